@@ -0,0 +1,97 @@
+//! `--bench`: headless stepping throughput and per-system timings.
+
+use bevy::app::MainScheduleOrder;
+use bevy::ecs::system::BoxedSystem;
+use bevy::prelude::*;
+use bevy::transform::systems::{propagate_transforms, sync_simple_transforms};
+use std::time::{Duration, Instant};
+use crate::{eclipse::*, headless_app, orbits::*, overlays::*, params::*, physics::*};
+
+/// A `run_bench` column: its heading, and how it changes the default params.
+type BenchScenario = (&'static str, fn(&mut SimulationParams));
+
+/// Scene variants `run_bench` times, each in a fresh `headless_app`: the bare scene, then
+/// the asteroid belt and n-body gravity added one at a time, to show what each costs.
+const BENCH_SCENARIOS: [BenchScenario; 3] = [
+    ("bare", |params| params.belt_count = 0),
+    ("+ belt", |_| {}), // the default belt
+    ("+ n-body", |params| {
+        params.belt_count = 0;
+        params.physics_mode = true;
+    }),
+];
+
+/// The per-frame simulation systems `run_bench` also times one by one, boxed so they can
+/// run outside their schedules.
+fn bench_systems() -> Vec<BoxedSystem> {
+    vec![
+        Box::new(IntoSystem::into_system(animate_orbits)),
+        Box::new(IntoSystem::into_system(spin_bodies)),
+        Box::new(IntoSystem::into_system(animate_elliptical_orbits)),
+        Box::new(IntoSystem::into_system(apply_rotations)),
+        Box::new(IntoSystem::into_system(orient_moon_plane)),
+        Box::new(IntoSystem::into_system(enforce_orbit_radii)),
+        Box::new(IntoSystem::into_system(nbody_gravity)),
+        Box::new(IntoSystem::into_system(sync_simple_transforms)),
+        Box::new(IntoSystem::into_system(propagate_transforms)),
+        Box::new(IntoSystem::into_system(record_trails)),
+        Box::new(IntoSystem::into_system(eclipse_detection)),
+        Box::new(IntoSystem::into_system(track_motion)),
+        Box::new(IntoSystem::into_system(detect_overlaps)),
+    ]
+}
+
+/// `--bench [STEPS]`: for each of `BENCH_SCENARIOS`, steps a headless app `steps` times
+/// after a short warm-up, running the frame's schedules by hand to time each, then runs
+/// each of `bench_systems` `steps` more times on its own for a per-system cost. Prints one
+/// table (a column per scenario) to stdout. The isolated runs skip the executor's overhead
+/// and keep moving the scene, which doesn't matter as each world is thrown away.
+pub(crate) fn run_bench(steps: u32) {
+    const WARM_UP: u32 = 10; // spawn the scene and let first-frame work settle
+    let steps = steps.max(1);
+    let mut columns = Vec::new(); // per scenario: steps/s, then (row label, total time)
+    for (_, configure) in BENCH_SCENARIOS {
+        let mut app = headless_app();
+        configure(&mut app.world_mut().resource_mut::<SimulationParams>());
+        for _ in 0..WARM_UP {
+            app.update();
+        }
+        let world = app.world_mut();
+        let labels = world.resource::<MainScheduleOrder>().labels.clone();
+        let mut timings: Vec<(String, Duration)> = labels.iter().map(|label| (format!("{label:?}"), Duration::ZERO)).collect();
+        let start = Instant::now();
+        for _ in 0..steps {
+            // What `Main` does each frame, with a stopwatch around every schedule
+            for (&label, (_, spent)) in labels.iter().zip(&mut timings) {
+                let schedule_start = Instant::now();
+                let _ = world.try_run_schedule(label);
+                *spent += schedule_start.elapsed();
+            }
+            world.clear_trackers();
+        }
+        let rate = f64::from(steps) / start.elapsed().as_secs_f64();
+        for mut system in bench_systems() {
+            system.initialize(world);
+            let system_start = Instant::now();
+            for _ in 0..steps {
+                system.run((), world);
+            }
+            let name = system.name();
+            timings.push((name.rsplit("::").next().unwrap_or(&name).to_string(), system_start.elapsed()));
+        }
+        columns.push((rate, timings));
+    }
+
+    let header: String = BENCH_SCENARIOS.iter().map(|(name, _)| format!("{name:>12}")).collect();
+    println!("{:<30}{header}", format!("{steps} steps of 1/60 s"));
+    let rates: String = columns.iter().map(|(rate, _)| format!("{rate:>12.0}")).collect();
+    println!("{:<30}{rates}", "steps/s");
+    println!("µs per step:");
+    for (row, (label, _)) in columns[0].1.iter().enumerate() {
+        let cells: String = columns
+            .iter()
+            .map(|(_, timings)| format!("{:>12.1}", timings[row].1.as_secs_f64() * 1e6 / f64::from(steps)))
+            .collect();
+        println!("  {label:<28}{cells}");
+    }
+}
@@ -0,0 +1,700 @@
+//! Cameras: the orbit controller, presets, follow mode, picking, and the minimap.
+
+use bevy::color::palettes::css;
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::ecs::system::SystemParam;
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::render::camera::{ScalingMode, Viewport};
+use bevy::render::view::RenderLayers;
+use bevy::window::PrimaryWindow;
+use bevy_egui::EguiContexts;
+use std::f32::consts::{PI, TAU};
+use crate::{orbits::*, params::*, render::*, scene::*};
+
+/// Component: the top-down orthographic inset camera, framed by `update_minimap`.
+#[derive(Component)]
+pub(crate) struct MinimapCamera;
+
+/// Resource: body picked by clicking in the viewport (`None` = nothing selected).
+#[derive(Resource, Default)]
+pub(crate) struct Selected(pub(crate) Option<Entity>);
+
+/// Named camera viewpoints from the UI's preset buttons.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum CameraPreset {
+    Default, // the startup perspective
+    Top,     // straight down on the whole system
+    Side,    // edge-on, in the ecliptic plane
+    Earth,   // close-up on Earth and the Moon, following Earth
+}
+
+impl CameraPreset {
+    pub(crate) const ALL: [CameraPreset; 4] = [CameraPreset::Default, CameraPreset::Top, CameraPreset::Side, CameraPreset::Earth];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            CameraPreset::Default => "Default",
+            CameraPreset::Top => "Top",
+            CameraPreset::Side => "Side",
+            CameraPreset::Earth => "Earth close-up",
+        }
+    }
+
+    /// The rig for this viewpoint; `earth` is Earth's current world position.
+    fn controller(self, earth: Vec3) -> CameraController {
+        match self {
+            CameraPreset::Default => CameraController::default(),
+            CameraPreset::Top => CameraController { yaw: 0.0, pitch: CameraController::MAX_PITCH, distance: 45.0, target: Vec3::ZERO },
+            CameraPreset::Side => CameraController { yaw: 0.0, pitch: 0.0, distance: 40.0, target: Vec3::ZERO },
+            CameraPreset::Earth => CameraController { yaw: 0.6, pitch: 0.35, distance: 3.5, target: earth },
+        }
+    }
+}
+
+/// Event: a camera preset button was clicked; handled by `apply_camera_preset`.
+#[derive(Event, Clone, Copy)]
+pub(crate) struct CameraPresetRequest(pub(crate) CameraPreset);
+
+/// Event: the UI's "Frame all" button; handled by `frame_all_bodies`.
+#[derive(Event, Clone, Copy)]
+pub(crate) struct FrameAllRequest;
+
+/// Resource: the rig `tween_camera` is easing the camera toward, if any.
+#[derive(Resource, Default)]
+pub(crate) struct CameraTween(pub(crate) Option<CameraController>);
+
+/// Resource: what drives the orbit camera's look-at target.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CameraMode {
+    #[default]
+    Free,           // target only moves when panning
+    Follow(Entity), // target glides after this body; yaw/pitch/distance still apply
+}
+
+/// Nearest hit distance along `ray` against a sphere, if any (origin inside counts).
+fn ray_sphere(ray: Ray3d, center: Vec3, radius: f32) -> Option<f32> {
+    let oc = ray.origin - center;
+    let b = oc.dot(*ray.direction);
+    let disc = b * b - (oc.length_squared() - radius * radius);
+    if disc < 0.0 {
+        return None;
+    }
+    let sqrt = disc.sqrt();
+    [-b - sqrt, -b + sqrt].into_iter().find(|&t| t >= 0.0)
+}
+
+/// Component: orbit-style camera rig. The camera sits on a sphere of radius `distance`
+/// around `target`, parameterized by azimuth (`yaw`) and elevation (`pitch`).
+#[derive(Component, Clone, Copy)]
+pub(crate) struct CameraController {
+    yaw: f32,      // azimuth around world Y (rad)
+    pitch: f32,    // elevation above the XZ plane (rad), clamped away from the poles
+    distance: f32, // camera–target distance
+    target: Vec3,  // look-at point, moved by panning
+}
+
+impl Default for CameraController {
+    /// The original fixed viewpoint: up and to the side, looking at the Sun.
+    fn default() -> Self {
+        Self::from_eye(Vec3::new(-6.0, 4.0, 8.0), Vec3::ZERO)
+    }
+}
+
+impl CameraController {
+    const MAX_PITCH: f32 = 1.54; // just under π/2 so `looking_at` never flips
+    const MIN_DISTANCE: f32 = 1.5;
+    const MAX_DISTANCE: f32 = 100.0;
+    const ROTATE_SPEED: f32 = 0.005; // rad per pixel dragged
+    const PAN_SPEED: f32 = 0.0015;   // fraction of `distance` per pixel dragged
+    const ZOOM_SPEED: f32 = 0.1;     // fraction of `distance` per scroll line
+
+    /// Builds a controller that reproduces a camera placed at `eye` looking at `target`.
+    fn from_eye(eye: Vec3, target: Vec3) -> Self {
+        let offset = eye - target;
+        let distance = offset.length();
+        Self {
+            yaw: offset.x.atan2(offset.z),
+            pitch: (offset.y / distance).asin(),
+            distance,
+            target,
+        }
+    }
+
+    /// Pushes the eye back along its line of sight to the target until it's outside the
+    /// sphere at `center`: if that line passes through the sphere at a point where the eye
+    /// would sit, the eye moves to where the line exits it, away from the target.
+    fn keep_eye_outside(&mut self, center: Vec3, radius: f32) {
+        let direction = (self.eye() - self.target) / self.distance;
+        let offset = self.target - center;
+        let b = offset.dot(direction);
+        let disc = b * b - (offset.length_squared() - radius * radius);
+        if disc <= 0.0 {
+            return;
+        }
+        let (enter, exit) = (-b - disc.sqrt(), -b + disc.sqrt());
+        if self.distance > enter && self.distance < exit {
+            self.distance = exit.min(Self::MAX_DISTANCE);
+        }
+    }
+
+    /// Keeps the viewing angle but aims at `center` from just far enough away that a sphere
+    /// of `radius` fits the narrower of the two fields of view, with a small margin.
+    fn framing(&self, center: Vec3, radius: f32, fov: f32, aspect_ratio: f32) -> Self {
+        const MARGIN: f32 = 1.1;
+        let half_vertical = fov / 2.0;
+        let half_horizontal = (half_vertical.tan() * aspect_ratio).atan();
+        let distance = MARGIN * radius / half_vertical.min(half_horizontal).sin();
+        Self { distance: distance.clamp(Self::MIN_DISTANCE, Self::MAX_DISTANCE), target: center, ..*self }
+    }
+
+    /// Moves a fraction `t` of the way to `goal`: yaw the short way round, distance in log
+    /// space so zooming feels even. The target is left alone unless `move_target` (in
+    /// follow mode `follow_camera` owns it).
+    fn ease_toward(&mut self, goal: &CameraController, t: f32, move_target: bool) {
+        let yaw_delta = (goal.yaw - self.yaw + PI).rem_euclid(TAU) - PI;
+        self.yaw += yaw_delta * t;
+        self.pitch += (goal.pitch - self.pitch) * t;
+        self.distance *= (goal.distance / self.distance).powf(t);
+        if move_target {
+            self.target = self.target.lerp(goal.target, t);
+        }
+    }
+
+    /// World-space camera position implied by yaw/pitch/distance.
+    fn eye(&self) -> Vec3 {
+        let (sy, cy) = self.yaw.sin_cos();
+        let (sp, cp) = self.pitch.sin_cos();
+        self.target + self.distance * Vec3::new(cp * sy, sp, cp * cy)
+    }
+}
+
+/// Startup system: spawns the main and minimap cameras and the fill light.
+pub(crate) fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    params: Res<SimulationParams>,
+) {
+    // Camera looking at world origin, driven by `orbit_camera` from here on
+    let controller = CameraController::default();
+    commands.spawn((
+        Camera3dBundle {
+            // HDR so the Sun's emissive can exceed 1.0 and spill into bloom
+            camera: Camera { hdr: true, ..default() },
+            transform: Transform::from_translation(controller.eye()).looking_at(controller.target, Vec3::Y),
+            ..default()
+        },
+        BloomSettings {
+            intensity: params.bloom_intensity,
+            ..BloomSettings::NATURAL
+        },
+        controller,
+        RenderLayers::from_layers(&[0, BACKDROP_LAYER]),
+    ))
+    .with_children(|camera| {
+        // Unlit so the Sun never shades it; colors and size are kept by `apply_background`
+        let mut quad = Mesh::from(Rectangle::new(1.0, 1.0));
+        quad.insert_attribute(Mesh::ATTRIBUTE_COLOR, gradient_colors(&quad, params.gradient_top, params.gradient_bottom));
+        camera.spawn((
+            PbrBundle {
+                mesh: meshes.add(quad),
+                material: materials.add(StandardMaterial {
+                    base_color: Color::WHITE,
+                    unlit: true,
+                    ..default()
+                }),
+                visibility: if params.gradient_background { Visibility::Inherited } else { Visibility::Hidden },
+                ..default()
+            },
+            GradientBackdrop,
+            NotShadowCaster,
+            RenderLayers::layer(BACKDROP_LAYER),
+        ));
+    });
+
+    // Upscales the main camera's image while it renders below window resolution, after
+    // the main camera and before the minimap, which draws on top of it
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera { order: 1, is_active: false, ..default() },
+            ..default()
+        },
+        SceneBlitCamera,
+        RenderLayers::layer(BLIT_LAYER),
+    ));
+    commands.spawn((SpriteBundle::default(), SceneBlit, RenderLayers::layer(BLIT_LAYER)));
+
+    // Minimap: a third camera drawn after the main one into a corner viewport, looking
+    // straight down Y (screen-up = -Z, matching the default view's sense of "ahead")
+    commands.spawn((
+        Camera3dBundle {
+            camera: Camera {
+                order: 2,
+                hdr: true,
+                is_active: params.show_minimap,
+                clear_color: ClearColorConfig::Custom(Color::srgb(0.02, 0.02, 0.05)),
+                ..default()
+            },
+            projection: OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical(40.0),
+                far: 2000.0,
+                ..default()
+            }
+            .into(),
+            transform: Transform::from_xyz(0.0, 500.0, 0.0).looking_at(Vec3::ZERO, Vec3::NEG_Z),
+            ..default()
+        },
+        MinimapCamera,
+    ));
+
+    // Optional directional fill light (off by default); the Sun's point light does the real work
+    commands.spawn(DirectionalLightBundle {
+        directional_light: DirectionalLight {
+            illuminance: params.fill_illuminance,
+            ..default()
+        },
+        transform: Transform::from_rotation(params.fill_light_rotation()),
+        ..default()
+    });
+
+}
+
+/// System: writes the FOV and clip-plane sliders into the camera's perspective projection.
+pub(crate) fn apply_camera_projection(mut q: Query<&mut Projection, With<CameraController>>, params: Res<SimulationParams>) {
+    if !params.is_changed() {
+        return;
+    }
+    for mut projection in &mut q {
+        if let Projection::Perspective(perspective) = &mut *projection {
+            perspective.fov = params.camera_fov_deg.to_radians();
+            perspective.near = params.camera_near;
+            perspective.far = params.camera_far.max(params.camera_near + 1.0);
+        }
+    }
+}
+
+/// System: toggles the minimap and keeps it square in the bottom-left corner (a third of
+/// the window's shorter side), zoomed to fit the outermost body. The corner is clear of
+/// the egui windows' default spots (Simulation top-left, Moon phase bottom-right, the
+/// selection panel on the right).
+pub(crate) fn update_minimap(
+    mut minimap_q: Query<(&mut Camera, &mut Projection), With<MinimapCamera>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    bodies: Query<&GlobalTransform, With<BodyRadius>>,
+    params: Res<SimulationParams>,
+) {
+    const MARGIN: u32 = 12; // px from the window edges
+    let Ok((mut camera, mut projection)) = minimap_q.get_single_mut() else { return };
+    let Ok(window) = windows.get_single() else { return };
+    let size = window.physical_size();
+    let side = size.x.min(size.y) / 3;
+    // A viewport outside the window is a render error, so tiny windows just hide it
+    let active = params.show_minimap && side >= 32 && size.y > side + MARGIN;
+    if camera.is_active != active {
+        camera.is_active = active;
+    }
+    if !active {
+        return;
+    }
+    camera.viewport = Some(Viewport {
+        physical_position: UVec2::new(MARGIN, size.y - side - MARGIN),
+        physical_size: UVec2::splat(side),
+        ..default()
+    });
+    let extent = bodies
+        .iter()
+        .map(|global| global.translation().xz().length())
+        .fold(1.0, f32::max);
+    if let Projection::Orthographic(ortho) = &mut *projection {
+        ortho.scaling_mode = ScalingMode::FixedVertical(2.0 * extent + 2.0);
+    }
+}
+
+/// System: left-click selects the body under the cursor (ray vs bounding sphere);
+/// clicking empty space clears the selection. A press that turns into a drag is
+/// left to `orbit_camera`, so rotating the view never changes the selection.
+pub(crate) fn pick_body(
+    mut selected: ResMut<Selected>,
+    mut press_position: Local<Option<Vec2>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<CameraController>>,
+    bodies: Query<PickableBody>,
+    mut contexts: EguiContexts,
+) {
+    const CLICK_SLOP: f32 = 4.0; // px the cursor may move and still count as a click
+    let Ok(window) = windows.get_single() else { return };
+    let Some(cursor) = window.cursor_position() else { return };
+    if buttons.just_pressed(MouseButton::Left) {
+        let ctx = contexts.ctx_mut();
+        *press_position = (!ctx.wants_pointer_input() && !ctx.is_pointer_over_area()).then_some(cursor);
+    }
+    if !buttons.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(pressed_at) = press_position.take() else { return };
+    if pressed_at.distance(cursor) > CLICK_SLOP {
+        return;
+    }
+    let Ok((camera, camera_transform)) = camera_q.get_single() else { return };
+    let Some(ray) = camera.viewport_to_world(camera_transform, cursor * viewport_ratio(camera, window)) else { return };
+    selected.0 = pick_nearest(ray, &bodies);
+}
+
+/// Query data for a body `pick_body` can hit.
+pub(crate) type PickableBody = (Entity, &'static GlobalTransform, &'static BodyRadius, &'static InheritedVisibility);
+
+/// Closest body hit by `ray`, accounting for each body's world scale. Hidden bodies are skipped.
+pub(crate) fn pick_nearest(ray: Ray3d, bodies: &Query<PickableBody>) -> Option<Entity> {
+    bodies
+        .iter()
+        .filter(|(.., visible)| visible.get())
+        .filter_map(|(entity, global, radius, _)| {
+            let scale = global.compute_transform().scale.max_element();
+            ray_sphere(ray, global.translation(), radius.0 * scale).map(|t| (entity, t))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity)
+}
+
+/// System: draws an outline sphere gizmo slightly larger than the selected body.
+pub(crate) fn highlight_selection(
+    mut gizmos: Gizmos,
+    selected: Res<Selected>,
+    bodies: Query<(&GlobalTransform, &BodyRadius)>,
+) {
+    let Some((global, radius)) = selected.0.and_then(|e| bodies.get(e).ok()) else { return };
+    let scale = global.compute_transform().scale.max_element();
+    gizmos.sphere(global.translation(), Quat::IDENTITY, radius.0 * scale * 1.15, css::YELLOW);
+}
+
+/// System: keeps the starfield centered on the camera so stars never get closer or parallax.
+pub(crate) fn follow_camera_skybox(
+    camera_q: Query<&Transform, (With<CameraController>, Without<Skybox>)>,
+    mut sky_q: Query<&mut Transform, With<Skybox>>,
+) {
+    let Ok(camera) = camera_q.get_single() else { return };
+    for mut sky in &mut sky_q {
+        sky.translation = camera.translation;
+    }
+}
+
+/// Frame-rate independent exponential smoothing factor shared by the follow-cam and
+/// preset tweens: at 8/s the camera is ~98% of the way there after half a second.
+fn camera_smoothing(dt: f32) -> f32 {
+    const RATE: f32 = 8.0; // 1/s; higher = tighter, lower = softer
+    1.0 - (-RATE * dt).exp()
+}
+
+/// System: services preset buttons: sets the tween goal, and switches the camera mode
+/// (the Earth close-up follows Earth, the rest are free). The goal's target is where
+/// Earth is now; follow mode keeps it centered after that.
+pub(crate) fn apply_camera_preset(
+    mut requests: EventReader<CameraPresetRequest>,
+    mut tween: ResMut<CameraTween>,
+    mut mode: ResMut<CameraMode>,
+    earth_q: Query<(Entity, &GlobalTransform), With<Earth>>,
+) {
+    let Some(&CameraPresetRequest(preset)) = requests.read().last() else { return };
+    let earth = earth_q.get_single().ok();
+    tween.0 = Some(preset.controller(earth.map_or(Vec3::ZERO, |(_, global)| global.translation())));
+    *mode = match (preset, earth) {
+        (CameraPreset::Earth, Some((entity, _))) => CameraMode::Follow(entity),
+        _ => CameraMode::Free,
+    };
+}
+
+/// System: "Frame all": fits a bounding sphere around every body (world position plus
+/// scaled radius, so resized orbits and bodies count as they are now) and tweens the camera,
+/// in free mode, to frame it through the current field of view.
+pub(crate) fn frame_all_bodies(
+    mut requests: EventReader<FrameAllRequest>,
+    mut tween: ResMut<CameraTween>,
+    mut mode: ResMut<CameraMode>,
+    bodies: Query<(&GlobalTransform, &BodyRadius)>,
+    camera_q: Query<(&CameraController, &Projection)>,
+) {
+    if requests.read().last().is_none() {
+        return;
+    }
+    let Ok((controller, Projection::Perspective(perspective))) = camera_q.get_single() else { return };
+    let spheres: Vec<(Vec3, f32)> = bodies
+        .iter()
+        .map(|(global, radius)| {
+            let (scale, _, translation) = global.to_scale_rotation_translation();
+            (translation, radius.0 * scale.max_element())
+        })
+        .collect();
+    if spheres.is_empty() {
+        return;
+    }
+    // Box center, then the farthest sphere surface from it: not minimal, but snug enough
+    let (min, max) = spheres
+        .iter()
+        .fold((Vec3::MAX, Vec3::MIN), |(min, max), &(p, r)| (min.min(p - r), max.max(p + r)));
+    let center = (min + max) / 2.0;
+    let radius = spheres.iter().map(|&(p, r)| p.distance(center) + r).fold(0.0, f32::max);
+    tween.0 = Some(controller.framing(center, radius, perspective.fov, perspective.aspect_ratio));
+    *mode = CameraMode::Free;
+}
+
+/// System: eases the camera rig toward the `CameraTween` goal with the follow-cam's
+/// smoothing, then drops the goal once it's reached. Grabbing the view with the mouse
+/// cancels it, so the tween never fights a drag.
+pub(crate) fn tween_camera(
+    mut tween: ResMut<CameraTween>,
+    mut camera_q: Query<&mut CameraController>,
+    mode: Res<CameraMode>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+) {
+    let Some(goal) = tween.0 else { return };
+    let ctx = contexts.ctx_mut();
+    let grabbed = buttons.any_pressed([MouseButton::Left, MouseButton::Right]);
+    if grabbed && !(ctx.wants_pointer_input() || ctx.is_pointer_over_area()) {
+        tween.0 = None;
+        return;
+    }
+    let move_target = *mode == CameraMode::Free;
+    let t = camera_smoothing(time.delta_seconds());
+    for mut ctrl in &mut camera_q {
+        ctrl.ease_toward(&goal, t, move_target);
+        let settled = ((goal.yaw - ctrl.yaw + PI).rem_euclid(TAU) - PI).abs() < 1e-3
+            && (ctrl.pitch - goal.pitch).abs() < 1e-3
+            && (ctrl.distance / goal.distance - 1.0).abs() < 1e-3
+            && (!move_target || ctrl.target.distance(goal.target) < 1e-3);
+        if settled {
+            ctrl.ease_toward(&goal, 1.0, move_target);
+            tween.0 = None;
+        }
+    }
+}
+
+/// System: centers the view on the `reference_frame` body. When the frame (or, after a
+/// reset, its entity) changes it switches the camera to follow that body, and every frame
+/// it carries the camera target along by the body's motion, so the body stays centered
+/// without the follow glide's lag and a free or panned camera still moves with the frame.
+pub(crate) fn apply_reference_frame(
+    params: Res<SimulationParams>,
+    mut mode: ResMut<CameraMode>,
+    mut camera_q: Query<&mut CameraController>,
+    bodies: Query<(Entity, &Name, &GlobalTransform), With<BodyRadius>>,
+    mut last: Local<Option<(Entity, Vec3)>>, // frame body and its position last frame
+) {
+    let frame = params.reference_frame.as_deref().and_then(|frame| bodies.iter().find(|(_, name, _)| name.as_str() == frame));
+    let Some((entity, _, global)) = frame else {
+        *last = None;
+        return;
+    };
+    let position = global.translation();
+    match *last {
+        Some((previous, last_position)) if previous == entity => {
+            for mut ctrl in &mut camera_q {
+                ctrl.target += position - last_position;
+            }
+        }
+        _ => *mode = CameraMode::Follow(entity),
+    }
+    *last = Some((entity, position));
+}
+
+/// System: in `CameraMode::Follow`, eases the camera target toward the followed body's
+/// world position. `orbit_camera` then places the camera at the usual yaw/pitch/distance
+/// offset from it, so the body stays centered while the mouse can still orbit around it.
+/// Falls back to free mode if the body no longer exists.
+pub(crate) fn follow_camera(
+    mut mode: ResMut<CameraMode>,
+    mut camera_q: Query<&mut CameraController>,
+    bodies: Query<&GlobalTransform>,
+    time: Res<Time>,
+) {
+    let CameraMode::Follow(entity) = *mode else { return };
+    let Ok(body) = bodies.get(entity) else {
+        *mode = CameraMode::Free;
+        return;
+    };
+    let t = camera_smoothing(time.delta_seconds());
+    for mut ctrl in &mut camera_q {
+        ctrl.target = ctrl.target.lerp(body.translation(), t);
+    }
+}
+
+/// Input `orbit_camera` reads, and what decides whether it's the camera's to take: egui
+/// owning the pointer, or Ctrl+scroll resizing the selection instead.
+#[derive(SystemParam)]
+pub(crate) struct CameraInput<'w, 's> {
+    motion: EventReader<'w, 's, MouseMotion>,
+    wheel: EventReader<'w, 's, MouseWheel>,
+    buttons: Res<'w, ButtonInput<MouseButton>>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    selected: Res<'w, Selected>,
+    contexts: EguiContexts<'w, 's>,
+}
+
+/// The bodies `orbit_camera` keeps clear of and rides along with.
+#[derive(SystemParam)]
+pub(crate) struct CameraAnchors<'w, 's> {
+    sun_q: Query<'w, 's, &'static GlobalTransform, With<Sun>>,
+    earth_q: Query<'w, 's, (&'static GlobalTransform, &'static TrackedMotion), With<Earth>>,
+}
+
+/// `orbit_camera`'s state between frames.
+#[derive(Default)]
+pub(crate) struct OrbitCameraState {
+    spin: Vec2,          // (yaw, pitch) rate in rad/s
+    along: (bool, Vec3), // look-along mode last frame, last known direction of travel
+}
+
+/// System: orbit camera. Left-drag rotates yaw/pitch around the target, right-drag pans
+/// the target in the view plane, and the scroll wheel changes distance.
+/// Input is ignored while the pointer is over the egui panel, but the transform is still
+/// refreshed so a followed target keeps moving.
+/// With `camera_inertia`, the rotate drag's angular velocity (smoothed over the last few
+/// frames, so pausing before letting go doesn't fling) carries on after release and decays
+/// at `camera_damping`; a preset tween stops it. Zoom stays within the controller's limits
+/// and never puts the eye inside the Sun. Ctrl+scroll with a selection is left to
+/// `resize_selection`.
+/// With `look_along_orbit` the target is pinned to Earth and yaw/pitch are measured in a
+/// frame riding along with it (see `orbit_frame`), starting from straight behind Earth,
+/// so the camera looks along its direction of travel. `camera_roll_deg` then turns the
+/// view about its line of sight in either mode.
+pub(crate) fn orbit_camera(
+    mut q: Query<(&mut CameraController, &mut Transform)>,
+    anchors: CameraAnchors,
+    input: CameraInput,
+    tween: Res<CameraTween>,
+    time: Res<Time>,
+    params: Res<SimulationParams>,
+    mut state: Local<OrbitCameraState>,
+) {
+    let CameraAnchors { sun_q, earth_q } = anchors;
+    let CameraInput { mut motion, mut wheel, buttons, keys, selected, mut contexts } = input;
+    let OrbitCameraState { spin, along } = &mut *state;
+    // Always drain the event readers so stale deltas don't pile up while the UI has focus
+    let mut drag: Vec2 = motion.read().map(|m| m.delta).sum();
+    let mut scroll = scroll_lines(&mut wheel);
+    if resizing_selection(&keys, &selected) {
+        scroll = 0.0;
+    }
+    let ctx = contexts.ctx_mut();
+    if ctx.wants_pointer_input() || ctx.is_pointer_over_area() {
+        drag = Vec2::ZERO;
+        scroll = 0.0;
+    }
+
+    let sun = sun_q.get_single().map_or(Vec3::ZERO, |sun| sun.translation());
+    let earth = earth_q.get_single().ok().filter(|_| params.look_along_orbit).map(|(global, motion)| {
+        // Paused or stepping backward through zero: keep facing the last way Earth moved
+        if motion.velocity.length_squared() > f32::EPSILON {
+            along.1 = motion.velocity.normalize();
+        }
+        if along.1 == Vec3::ZERO {
+            along.1 = Vec3::Y.cross(global.translation() - sun).normalize_or_zero();
+        }
+        (global.translation(), orbit_frame(global.translation() - sun, along.1))
+    });
+    let entering = earth.is_some() && !along.0;
+    along.0 = earth.is_some();
+
+    let dt = time.delta_seconds();
+    for (mut ctrl, mut transform) in &mut q {
+        if entering {
+            (ctrl.yaw, ctrl.pitch) = (0.0, 0.2); // just behind and above Earth
+        }
+        let turn = if buttons.pressed(MouseButton::Left) {
+            let turn = Vec2::new(-drag.x, drag.y) * CameraController::ROTATE_SPEED;
+            if dt > 0.0 {
+                *spin = spin.lerp(turn / dt, 0.5);
+            }
+            turn
+        } else if params.camera_inertia && tween.0.is_none() {
+            let turn = *spin * dt;
+            *spin *= (-params.camera_damping * dt).exp();
+            turn
+        } else {
+            *spin = Vec2::ZERO;
+            Vec2::ZERO
+        };
+        ctrl.yaw += turn.x;
+        ctrl.pitch = (ctrl.pitch + turn.y).clamp(-CameraController::MAX_PITCH, CameraController::MAX_PITCH);
+        if buttons.pressed(MouseButton::Right) {
+            // Pan in the camera's screen plane; scale by distance so it feels the same at any zoom
+            let step = ctrl.distance * CameraController::PAN_SPEED;
+            let pan = (-drag.x * *transform.right() + drag.y * *transform.up()) * step;
+            ctrl.target += pan;
+        }
+        if scroll != 0.0 {
+            ctrl.distance = (ctrl.distance * (1.0 - scroll * CameraController::ZOOM_SPEED))
+                .clamp(CameraController::MIN_DISTANCE, CameraController::MAX_DISTANCE);
+        }
+        if let Ok(sun) = sun_q.get_single() {
+            ctrl.keep_eye_outside(sun.translation(), params.sun_radius * 1.1);
+        }
+        let frame = match earth {
+            Some((position, frame)) => {
+                ctrl.target = position;
+                frame
+            }
+            None => Quat::IDENTITY,
+        };
+        let eye = ctrl.target + frame * (ctrl.eye() - ctrl.target);
+        *transform = Transform::from_translation(eye).looking_at(ctrl.target, frame * Vec3::Y);
+        transform.rotate_local_z(params.camera_roll_deg.to_radians());
+    }
+}
+
+/// Rotation from the camera's world frame into one riding along an orbit: local −Z along
+/// the direction of travel `forward`, local +Y along the orbit normal (on the +Y side of
+/// the ecliptic, whichever way the body goes round), local +X outward-ish. `radial` is the
+/// body's offset from the Sun.
+fn orbit_frame(radial: Vec3, forward: Vec3) -> Quat {
+    let mut up = radial.cross(forward).normalize_or(Vec3::Y);
+    if up.y < 0.0 {
+        up = -up;
+    }
+    let forward = forward.reject_from_normalized(up).normalize_or(Vec3::NEG_Z);
+    Quat::from_mat3(&Mat3::from_cols(forward.cross(up), up, -forward))
+}
+
+/// This frame's mouse-wheel movement in lines (pixel deltas from touchpads ≈ 100 px a line).
+fn scroll_lines(wheel: &mut EventReader<MouseWheel>) -> f32 {
+    wheel
+        .read()
+        .map(|w| match w.unit {
+            MouseScrollUnit::Line => w.y,
+            MouseScrollUnit::Pixel => w.y / 100.0,
+        })
+        .sum()
+}
+
+/// Whether the wheel resizes the selected body (Ctrl held with a selection) instead of zooming.
+fn resizing_selection(keys: &ButtonInput<KeyCode>, selected: &Selected) -> bool {
+    selected.0.is_some() && keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+}
+
+/// System: Ctrl+scroll over the scene grows or shrinks the selected body by
+/// `SizeScale::STEP` per wheel line, within `SizeScale::RANGE`. Bodies without a
+/// `TrueRadius` (asteroids, comet, extra moons) keep their size.
+pub(crate) fn resize_selection(
+    mut commands: Commands,
+    mut wheel: EventReader<MouseWheel>,
+    keys: Res<ButtonInput<KeyCode>>,
+    selected: Res<Selected>,
+    sizes: Query<Option<&SizeScale>, With<TrueRadius>>,
+    mut contexts: EguiContexts,
+) {
+    let scroll = scroll_lines(&mut wheel);
+    let ctx = contexts.ctx_mut();
+    if scroll == 0.0 || !resizing_selection(&keys, &selected) || ctx.wants_pointer_input() || ctx.is_pointer_over_area() {
+        return;
+    }
+    let Some(entity) = selected.0 else { return };
+    let Ok(size) = sizes.get(entity) else { return };
+    let (min, max) = SizeScale::RANGE.into_inner();
+    let factor = (size.map_or(1.0, |s| s.0) * SizeScale::STEP.powf(scroll)).clamp(min, max);
+    // `try_insert`: a reset may despawn the entity before the command applies
+    commands.entity(entity).try_insert(SizeScale(factor));
+}
@@ -0,0 +1,240 @@
+//! Eclipses and Sun–Earth–Moon alignments.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{PI, TAU};
+use crate::{params::*, scene::*};
+
+/// Which body is being eclipsed, as seen from Earth.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub(crate) enum Eclipse {
+    #[default]
+    None,
+    Solar, // Moon between Sun and Earth: Earth is shadowed
+    Lunar, // Earth between Sun and Moon: Moon is shadowed
+}
+
+/// Which Sun–Earth–Moon line-up `pause_at_alignment` waits for (in ecliptic longitude,
+/// so it triggers every month; a real eclipse also needs the Moon near a node).
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum AlignmentTarget {
+    #[default]
+    Off,
+    NewMoon,  // Moon between Sun and Earth, like a solar eclipse
+    FullMoon, // Earth between Sun and Moon, like a lunar eclipse
+}
+
+impl AlignmentTarget {
+    pub(crate) const ALL: [AlignmentTarget; 3] = [AlignmentTarget::Off, AlignmentTarget::NewMoon, AlignmentTarget::FullMoon];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            AlignmentTarget::Off => "Off",
+            AlignmentTarget::NewMoon => "New moon (solar-eclipse-like)",
+            AlignmentTarget::FullMoon => "Full moon (lunar-eclipse-like)",
+        }
+    }
+
+    /// Angle (rad) from this alignment for a signed elongation in [−π, π].
+    fn misalignment(self, elongation: f32) -> f32 {
+        match self {
+            AlignmentTarget::Off => PI,
+            AlignmentTarget::NewMoon => elongation.abs(),
+            AlignmentTarget::FullMoon => PI - elongation.abs(),
+        }
+    }
+}
+
+/// Resource: eclipse state maintained by `eclipse_detection`.
+#[derive(Resource, Default)]
+pub(crate) struct EclipseState {
+    pub(crate) current: Eclipse,
+    pub(crate) last: Option<(Eclipse, f64)>, // most recent eclipse and its `SimClock::sim_seconds`
+    prev_elongation: Option<f32>, // last frame's elongation, to catch alignments crossed between frames
+    pub(crate) umbra_on_earth: bool,      // the Moon's full shadow touches Earth (a total eclipse somewhere)
+    pub(crate) moon_shadow_texels: f32,   // the Moon's width in the Sun's shadow map, ≈ its shadow's width in texels
+}
+
+/// Whether the Moon's umbra, the cone of full shadow behind it that narrows to a point where
+/// the Moon just covers the Sun, reaches Earth's sphere. Worked in a plane through the
+/// cone's axis, where the umbra is the triangle (0, 0), (0, r_moon), (length, 0) in
+/// (distance behind the Moon, distance off the axis): Earth touches it when its center is
+/// within `earth_radius` of that triangle. A Moon as big as the Sun casts an endless umbra.
+fn umbra_reaches(sun: (Vec3, f32), moon: (Vec3, f32), earth: (Vec3, f32)) -> bool {
+    const ENDLESS: f32 = 1e4; // far past any orbit
+    let ((sun, sun_radius), (moon, moon_radius), (earth, earth_radius)) = (sun, moon, earth);
+    let Some(axis) = (moon - sun).try_normalize() else { return false };
+    let length = if sun_radius > moon_radius { sun.distance(moon) * moon_radius / (sun_radius - moon_radius) } else { ENDLESS };
+    let offset = earth - moon;
+    let along = offset.dot(axis);
+    let point = Vec2::new(along, (offset - axis * along).length());
+    let to_segment = |a: Vec2, b: Vec2| {
+        let t = ((point - a).dot(b - a) / (b - a).length_squared()).clamp(0.0, 1.0);
+        point.distance(a + (b - a) * t)
+    };
+    let (apex, rim) = (Vec2::new(length.min(ENDLESS), 0.0), Vec2::new(0.0, moon_radius));
+    let inside = (0.0..=apex.x).contains(&point.x) && point.y <= moon_radius * (1.0 - point.x / apex.x);
+    inside || to_segment(Vec2::ZERO, rim).min(to_segment(rim, apex)).min(to_segment(Vec2::ZERO, apex)) < earth_radius
+}
+
+/// Sun–Earth–Moon geometry seen from Earth, relative to the ecliptic (XZ) plane.
+pub(crate) struct Syzygy {
+    pub(crate) elongation: f32, // signed Sun→Moon angle around Earth in the ecliptic: 0 = new moon, ±π = full moon
+    pub(crate) latitude: f32,   // Moon's ecliptic latitude; only near zero close to the nodes of its inclined orbit
+}
+
+impl Syzygy {
+    pub(crate) fn new(sun: Vec3, earth: Vec3, moon: Vec3) -> Self {
+        let to_moon = moon - earth;
+        Self {
+            elongation: (sun - earth).xz().angle_between(to_moon.xz()),
+            latitude: (to_moon.y / to_moon.length()).asin(),
+        }
+    }
+
+    /// Eclipse kind for this geometry, if the Moon is aligned within `threshold` (rad)
+    /// both in elongation and in latitude (i.e., near a node).
+    fn eclipse(&self, threshold: f32) -> Eclipse {
+        if self.latitude.abs() > threshold {
+            Eclipse::None
+        } else if self.elongation.abs() < threshold {
+            Eclipse::Solar
+        } else if PI - self.elongation.abs() < threshold {
+            Eclipse::Lunar
+        } else {
+            Eclipse::None
+        }
+    }
+
+    /// Lunar phase as a fraction of a synodic month: 0 = new, 0.25 = first quarter, 0.5 = full.
+    /// Prograde motion drives the elongation negative after new moon, hence the sign flip.
+    pub(crate) fn phase(&self) -> f32 {
+        (-self.elongation / TAU).rem_euclid(1.0)
+    }
+
+    /// Fraction of the Moon's disc that is lit as seen from Earth.
+    pub(crate) fn illuminated_fraction(&self) -> f32 {
+        (1.0 - self.elongation.cos()) / 2.0
+    }
+}
+
+/// Moon phase names, in order of increasing `Syzygy::phase`.
+pub(crate) const MOON_PHASES: [&str; 8] = [
+    "New moon",
+    "Waxing crescent",
+    "First quarter",
+    "Waxing gibbous",
+    "Full moon",
+    "Waning gibbous",
+    "Last quarter",
+    "Waning crescent",
+];
+
+/// State for `pause_at_alignment`.
+#[derive(Default)]
+pub(crate) struct AlignmentWatch {
+    prev_elongation: Option<f32>,
+    armed: bool,         // cleared on a trigger, set again once the bodies drift apart
+    highlight: f32,      // seconds (wall clock) left on the line-up highlight
+}
+
+/// System: pause-at-alignment. Watches the Sun–Earth–Moon elongation and pauses the
+/// moment it comes within `eclipse_threshold_deg` of the chosen line-up, or crosses it
+/// between frames (the Moon moves several degrees per frame at default speed). After a
+/// trigger it stays disarmed until the bodies are well apart, so unpausing doesn't stop
+/// again straight away. A fading gizmo line through the three bodies marks the catch.
+pub(crate) fn pause_at_alignment(
+    mut gizmos: Gizmos,
+    mut watch: Local<AlignmentWatch>,
+    mut params: ResMut<SimulationParams>,
+    sun_q: Query<&GlobalTransform, With<Sun>>,
+    earth_q: Query<&GlobalTransform, With<Earth>>,
+    moon_q: Query<&GlobalTransform, With<Moon>>,
+    real_time: Res<Time<Real>>,
+) {
+    const REARM_DEG: f32 = 20.0; // misalignment needed before the next trigger
+    const HIGHLIGHT_SECS: f32 = 2.0;
+    let (Ok(sun), Ok(earth), Ok(moon)) = (sun_q.get_single(), earth_q.get_single(), moon_q.get_single()) else { return };
+    let (sun, earth, moon) = (sun.translation(), earth.translation(), moon.translation());
+    let elongation = Syzygy::new(sun, earth, moon).elongation;
+    let target = params.pause_at;
+    let misalignment = target.misalignment(elongation);
+    let crossed = watch.prev_elongation.is_some_and(|prev| {
+        // Same sign flip `eclipse_detection` uses: near 0 for new moon, near ±π for full
+        prev.signum() != elongation.signum() && target.misalignment(prev) < PI / 2.0
+    });
+    watch.prev_elongation = Some(elongation);
+    if misalignment > REARM_DEG.to_radians() && !crossed {
+        watch.armed = true;
+    }
+    let reached = misalignment <= params.eclipse_threshold_deg.to_radians() || crossed;
+    if target != AlignmentTarget::Off && watch.armed && reached && !params.paused {
+        params.paused = true;
+        watch.armed = false;
+        watch.highlight = HIGHLIGHT_SECS;
+    }
+
+    if watch.highlight > 0.0 {
+        watch.highlight = (watch.highlight - real_time.delta_seconds()).max(0.0);
+        let color = Color::srgba(1.0, 0.85, 0.3, 0.8 * watch.highlight / HIGHLIGHT_SECS);
+        let (near, far) = if (moon - sun).length() < (earth - sun).length() { (moon, earth) } else { (earth, moon) };
+        gizmos.linestrip([sun, near, far], color);
+    }
+}
+
+/// Query data for a body whose material can be tinted and later restored.
+type TintableBody = (&'static GlobalTransform, &'static Handle<StandardMaterial>, &'static BaseColor, &'static BodyRadius);
+
+/// System: classifies the current Sun–Earth–Moon alignment as a solar/lunar eclipse.
+/// The Moon moves several degrees per frame at default speeds, so an alignment crossed
+/// between two frames also counts. Optionally tints the eclipsed body; Earth only with
+/// shadows off, since darkening it all over would hide the Moon's shadow. Also works out from
+/// the bodies' world sizes whether the Moon's umbra touches Earth, and how many shadow-map
+/// texels the Moon (so its rendered shadow) spans: a cube face covers 90°, so a texel at
+/// distance d from the Sun is ≈ 2d / size across.
+pub(crate) fn eclipse_detection(
+    mut state: ResMut<EclipseState>,
+    sun_q: Query<(&GlobalTransform, &BodyRadius), With<Sun>>,
+    earth_q: Query<TintableBody, (With<Earth>, Without<Moon>)>,
+    moon_q: Query<TintableBody, (With<Moon>, Without<Earth>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    params: Res<SimulationParams>,
+    clock: Res<SimClock>,
+) {
+    let (Ok((sun, sun_radius)), Ok((earth, earth_mat, earth_base, earth_radius)), Ok((moon, moon_mat, moon_base, moon_radius))) =
+        (sun_q.get_single(), earth_q.get_single(), moon_q.get_single())
+    else {
+        return;
+    };
+    let sphere = |global: &GlobalTransform, radius: &BodyRadius| (global.translation(), radius.0 * global.compute_transform().scale.max_element());
+    let (sun_sphere, moon_sphere) = (sphere(sun, sun_radius), sphere(moon, moon_radius));
+    let umbra_on_earth = umbra_reaches(sun_sphere, moon_sphere, sphere(earth, earth_radius));
+    let moon_shadow_texels = moon_sphere.1 * params.shadow_map_size as f32 / sun_sphere.0.distance(moon_sphere.0).max(f32::EPSILON);
+    if (state.umbra_on_earth, state.moon_shadow_texels) != (umbra_on_earth, moon_shadow_texels) {
+        (state.umbra_on_earth, state.moon_shadow_texels) = (umbra_on_earth, moon_shadow_texels);
+    }
+    let geometry = Syzygy::new(sun.translation(), earth.translation(), moon.translation());
+    let threshold = params.eclipse_threshold_deg.to_radians();
+    let mut current = geometry.eclipse(threshold);
+
+    // Sign flip of the elongation between frames = an alignment was crossed
+    if let (Eclipse::None, Some(prev)) = (current, state.prev_elongation) {
+        let crossed = prev.signum() != geometry.elongation.signum();
+        if crossed && geometry.latitude.abs() <= threshold {
+            current = if prev.abs() < PI / 2.0 { Eclipse::Solar } else { Eclipse::Lunar };
+        }
+    }
+    state.prev_elongation = Some(geometry.elongation);
+    if current != Eclipse::None && state.current == Eclipse::None {
+        state.last = Some((current, clock.sim_seconds));
+    }
+    state.current = current;
+
+    let tint = |eclipse| params.eclipse_tint && current == eclipse;
+    if let Some(mat) = materials.get_mut(earth_mat) {
+        mat.base_color = if tint(Eclipse::Solar) && !params.shadows { earth_base.0.mix(&Color::BLACK, 0.6) } else { earth_base.0 };
+    }
+    if let Some(mat) = materials.get_mut(moon_mat) {
+        mat.base_color = if tint(Eclipse::Lunar) { Color::srgb(0.6, 0.15, 0.1) } else { moon_base.0 };
+    }
+}
@@ -0,0 +1,665 @@
+//! Screenshots, glTF and CSV export, and saved simulation state.
+
+use bevy::prelude::*;
+use bevy::render::mesh::PrimitiveTopology;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::render::view::RenderLayers;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::{orbits::*, overlays::*, params::*, scene::*, ui::*};
+
+/// File used by the Save/Load buttons, relative to the working directory.
+const STATE_FILE: &str = "simulation_state.ron";
+
+/// Event: sent by the UI, handled by `handle_state_file`.
+#[derive(Event, Clone, Copy)]
+pub(crate) enum StateFileRequest {
+    Save,
+    Load,
+}
+
+/// Event: sent by F12 or the UI's "Screenshot" button, handled by `take_screenshot`.
+#[derive(Event, Clone, Copy)]
+pub(crate) struct ScreenshotRequest;
+
+/// Event: sent by the UI's "Export glTF" button, handled by `export_gltf`.
+#[derive(Event, Clone, Copy)]
+pub(crate) struct ExportGltfRequest;
+
+/// Resource: screenshot options plus a capture waiting for the UI to disappear.
+#[derive(Resource, Default)]
+pub(crate) struct ScreenshotSettings {
+    pub(crate) include_ui: bool, // keep the egui windows/labels in the image
+    hiding_ui: bool,  // UI is skipped this frame so the next capture is scene-only
+}
+
+/// Run condition: false for the one frame a UI-less screenshot is being taken, and while
+/// the window is minimized (egui would lay its windows out in a zero-size screen).
+pub(crate) fn ui_visible(screenshot: Res<ScreenshotSettings>, clock: Res<SimClock>) -> bool {
+    !screenshot.hiding_ui && !clock.suspended
+}
+
+/// Resource: CSV position logging, toggled from the UI and written by `log_positions`.
+/// Columns are fixed when a file is opened (every named body present then); a body that
+/// disappears later just leaves its cells empty.
+#[derive(Resource)]
+pub(crate) struct CsvExport {
+    pub(crate) record_csv: bool,
+    pub(crate) path: String,
+    pub(crate) interval: f32, // real seconds between rows; 0 = every frame
+    pub(crate) writer: Option<BufWriter<File>>,
+    columns: Vec<Entity>,
+    since_sample: f32,
+    since_flush: f32,
+}
+
+impl Default for CsvExport {
+    fn default() -> Self {
+        Self {
+            record_csv: false,
+            path: "orbits.csv".into(),
+            interval: 0.0,
+            writer: None,
+            columns: Vec::new(),
+            since_sample: 0.0,
+            since_flush: 0.0,
+        }
+    }
+}
+
+impl CsvExport {
+    /// Flushes and closes the current file, if any.
+    fn close(&mut self) {
+        if let Some(mut writer) = self.writer.take() {
+            if let Err(err) = writer.flush() {
+                warn!("Failed to flush {}: {err}", self.path);
+            }
+            info!("Closed {}", self.path);
+        }
+    }
+}
+
+/// On-disk snapshot: the sliders, the clock, and every named body's pose.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SavedState {
+    params: SimulationParams,
+    sim_seconds: f64,
+    earth_orbit_angle: f64,
+    bodies: Vec<SavedBody>,
+}
+
+/// One body's pose. Vectors are plain arrays so the file format doesn't depend on glam's serde.
+#[derive(Serialize, Deserialize)]
+struct SavedBody {
+    name: String,
+    translation: [f32; 3],
+    rotation: [f32; 4],
+    pivot_rotation: Option<[f32; 4]>, // parent `Orbit` pivot rotation = current orbital angle
+    elliptical: Option<SavedEllipse>,
+    #[serde(default)]
+    angles: Option<SavedAngles>, // absent in files saved before f64 angle tracking
+}
+
+/// The t = 0 poses and f64 angles `apply_rotations` rebuilds the rotations above from.
+#[derive(Serialize, Deserialize)]
+struct SavedAngles {
+    pose: [f32; 4],
+    spin: f64,
+    axis_yaw: f64,
+    pivot: Option<([f32; 4], f64)>, // parent pivot's `InitialPose` and yaw
+}
+
+/// `EllipticalOrbit` state variables (the shape itself comes from the params and `PLANETS`).
+#[derive(Serialize, Deserialize)]
+struct SavedEllipse {
+    theta: f32,
+    mean_anomaly: f32,
+}
+
+/// Writes `state` to `path` as pretty-printed RON.
+fn save_state(path: &str, state: &SavedState) -> Result<(), String> {
+    let text = ron::ser::to_string_pretty(state, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+/// Reads a snapshot previously written by `save_state`.
+pub(crate) fn load_state(path: &str) -> Result<SavedState, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    ron::from_str(&text).map_err(|e| e.to_string())
+}
+
+/// Query data for a named body that can be snapshotted/restored.
+type SaveableBody = (
+    &'static Name,
+    &'static mut Transform,
+    Option<&'static Parent>,
+    Option<&'static mut EllipticalOrbit>,
+    &'static mut Spin,
+    Option<&'static mut InitialPose>,
+);
+
+/// System: F12 (see `KEY_BINDINGS`) or a `ScreenshotRequest` saves the primary window to a timestamped PNG in
+/// the working directory. Without "Include UI", the request first hides the UI for a frame
+/// (`ui_visible`) and the capture happens on that frame instead.
+pub(crate) fn take_screenshot(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut requests: EventReader<ScreenshotRequest>,
+    mut settings: ResMut<ScreenshotSettings>,
+    mut manager: ResMut<ScreenshotManager>,
+    window_q: Query<Entity, With<PrimaryWindow>>,
+) {
+    let requested = requests.read().count() > 0 || keys.any_just_pressed(KeyAction::Screenshot.keys());
+    if !settings.hiding_ui {
+        if !requested {
+            return;
+        }
+        if !settings.include_ui {
+            settings.hiding_ui = true;
+            return;
+        }
+    }
+    settings.hiding_ui = false;
+    let Ok(window) = window_q.get_single() else { return };
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let path = format!("screenshot-{}-{:03}.png", stamp.as_secs(), stamp.subsec_millis());
+    match manager.save_screenshot_to_disk(window, &path) {
+        Ok(()) => info!("Saved screenshot to {path}"),
+        Err(err) => warn!("Screenshot skipped: {err}"),
+    }
+}
+
+/// Query data for an entity `export_gltf` may write out as a glTF node.
+type GltfNode = (
+    &'static GlobalTransform,
+    &'static Handle<Mesh>,
+    &'static Handle<StandardMaterial>,
+    &'static InheritedVisibility,
+    Option<&'static Name>,
+    Option<&'static RenderLayers>,
+);
+
+/// System: an `ExportGltfRequest` (the "Export glTF" button) writes a snapshot of the
+/// scene to a timestamped `.glb` in the working directory. Runs after transform
+/// propagation, so every node gets this frame's world transform (flat, no hierarchy).
+/// Only visible entities on the main render layer with a `StandardMaterial` are written,
+/// which leaves out the starfield and backdrop; meshes and materials shared between
+/// entities (the belt's rocks) are written once. Textures aren't embedded: images are
+/// referenced by their path under `assets/`, so keep the `.glb` next to that folder.
+pub(crate) fn export_gltf(
+    mut requests: EventReader<ExportGltfRequest>,
+    nodes: Query<GltfNode, Without<Skybox>>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+) {
+    if requests.read().count() == 0 {
+        return;
+    }
+    let mut export = GltfExport::default();
+    let mut skipped = 0;
+    for (global, mesh, material, visible, name, layers) in &nodes {
+        if !visible.get() || layers.is_some_and(|layers| !layers.intersects(&RenderLayers::default())) {
+            continue;
+        }
+        let Some(mesh) = export.mesh(mesh, &meshes) else {
+            skipped += 1; // not a triangle list, or its data only lives in the render world
+            continue;
+        };
+        let material = export.material(material, &materials, &asset_server);
+        let mesh = export.with_material(mesh, material);
+        let (scale, rotation, translation) = global.to_scale_rotation_translation();
+        export.nodes.push(serde_json::json!({
+            "name": name.map_or("Mesh", |name| name.as_str()),
+            "mesh": mesh,
+            "translation": translation.to_array(),
+            "rotation": rotation.to_array(),
+            "scale": scale.to_array(),
+        }));
+    }
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let path = format!("scene-{}-{:03}.glb", stamp.as_secs(), stamp.subsec_millis());
+    let count = export.nodes.len();
+    match std::fs::write(&path, export.into_glb()) {
+        Ok(()) if skipped > 0 => info!("Exported {count} meshes to {path} ({skipped} skipped: no exportable geometry)"),
+        Ok(()) => info!("Exported {count} meshes to {path}"),
+        Err(err) => warn!("glTF export to {path} failed: {err}"),
+    }
+}
+
+/// glTF 2.0 document being assembled by `export_gltf`: JSON arrays plus the one binary
+/// buffer every accessor points into. Meshes and materials are deduplicated by asset id,
+/// and each (mesh, material) pair becomes its own glTF mesh, since glTF binds materials
+/// per primitive.
+#[derive(Default)]
+struct GltfExport {
+    nodes: Vec<serde_json::Value>,
+    meshes: Vec<serde_json::Value>,
+    materials: Vec<serde_json::Value>,
+    accessors: Vec<serde_json::Value>,
+    buffer_views: Vec<serde_json::Value>,
+    images: Vec<String>, // uris, each with a texture of the same index
+    buffer: Vec<u8>,
+    geometry: Vec<(AssetId<Mesh>, Option<serde_json::Value>)>, // primitive attributes + indices
+    material_ids: Vec<AssetId<StandardMaterial>>,
+    primitives: Vec<(AssetId<Mesh>, usize)>, // (mesh, material) for each entry of `meshes`
+    uses_unlit: bool,
+    uses_emissive_strength: bool,
+}
+
+impl GltfExport {
+    const FLOAT: u32 = 5126;
+    const UNSIGNED_INT: u32 = 5125;
+    const ARRAY_BUFFER: u32 = 34962;
+    const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+    /// Appends `bytes` to the buffer (4-byte aligned) and returns its accessor index.
+    fn accessor(&mut self, bytes: &[u8], target: u32, accessor: serde_json::Value) -> usize {
+        self.buffer.resize(self.buffer.len().next_multiple_of(4), 0);
+        let mut accessor = accessor;
+        accessor["bufferView"] = self.buffer_views.len().into();
+        self.buffer_views.push(serde_json::json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": bytes.len(),
+            "target": target,
+        }));
+        self.buffer.extend_from_slice(bytes);
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    /// Vertex data of `handle` as a primitive's attributes and indices, written on first
+    /// use. None for meshes that aren't triangle lists or have no positions in the main world.
+    fn mesh(&mut self, handle: &Handle<Mesh>, meshes: &Assets<Mesh>) -> Option<AssetId<Mesh>> {
+        let id = handle.id();
+        if let Some((_, geometry)) = self.geometry.iter().find(|(known, _)| *known == id) {
+            return geometry.is_some().then_some(id);
+        }
+        let geometry = meshes.get(id).and_then(|mesh| self.geometry(mesh));
+        let found = geometry.is_some();
+        self.geometry.push((id, geometry));
+        found.then_some(id)
+    }
+
+    fn geometry(&mut self, mesh: &Mesh) -> Option<serde_json::Value> {
+        use bevy::render::mesh::VertexAttributeValues::{Float32x2, Float32x3};
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+        let Some(Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else { return None };
+        let (min, max) = positions.iter().fold((Vec3::MAX, Vec3::MIN), |(min, max), &p| (min.min(p.into()), max.max(p.into())));
+        let floats = |values: &[f32]| values.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<u8>>();
+        let mut attributes = serde_json::Map::new();
+        let count = positions.len();
+        let position = self.accessor(
+            &floats(positions.as_flattened()),
+            Self::ARRAY_BUFFER,
+            serde_json::json!({ "componentType": Self::FLOAT, "count": count, "type": "VEC3", "min": min.to_array(), "max": max.to_array() }),
+        );
+        attributes.insert("POSITION".into(), position.into());
+        if let Some(Float32x3(normals)) = mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+            let accessor = serde_json::json!({ "componentType": Self::FLOAT, "count": normals.len(), "type": "VEC3" });
+            attributes.insert("NORMAL".into(), self.accessor(&floats(normals.as_flattened()), Self::ARRAY_BUFFER, accessor).into());
+        }
+        if let Some(Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            let accessor = serde_json::json!({ "componentType": Self::FLOAT, "count": uvs.len(), "type": "VEC2" });
+            attributes.insert("TEXCOORD_0".into(), self.accessor(&floats(uvs.as_flattened()), Self::ARRAY_BUFFER, accessor).into());
+        }
+        let mut primitive = serde_json::json!({ "attributes": attributes });
+        if let Some(indices) = mesh.indices() {
+            let bytes: Vec<u8> = indices.iter().flat_map(|i| (i as u32).to_le_bytes()).collect();
+            let accessor = serde_json::json!({ "componentType": Self::UNSIGNED_INT, "count": indices.len(), "type": "SCALAR" });
+            primitive["indices"] = self.accessor(&bytes, Self::ELEMENT_ARRAY_BUFFER, accessor).into();
+        }
+        Some(primitive)
+    }
+
+    /// Index of `handle`'s glTF material, written on first use: base color (and texture
+    /// reference), metallic/roughness, emissive (HDR values through
+    /// `KHR_materials_emissive_strength`), alpha blending, double-sidedness and unlit.
+    fn material(&mut self, handle: &Handle<StandardMaterial>, materials: &Assets<StandardMaterial>, asset_server: &AssetServer) -> usize {
+        let id = handle.id();
+        if let Some(index) = self.material_ids.iter().position(|&known| known == id) {
+            return index;
+        }
+        let fallback = StandardMaterial::default();
+        let material = materials.get(id).unwrap_or(&fallback);
+        let mut pbr = serde_json::json!({
+            "baseColorFactor": material.base_color.to_linear().to_f32_array(),
+            "metallicFactor": material.metallic,
+            "roughnessFactor": material.perceptual_roughness,
+        });
+        let texture = material.base_color_texture.as_ref().and_then(|texture| asset_server.get_path(texture));
+        if let Some(path) = texture {
+            let uri = format!("assets/{}", path.path().to_string_lossy().replace('\\', "/"));
+            let index = self.images.iter().position(|known| *known == uri).unwrap_or_else(|| {
+                self.images.push(uri);
+                self.images.len() - 1
+            });
+            pbr["baseColorTexture"] = serde_json::json!({ "index": index });
+        }
+        let mut json = serde_json::json!({ "pbrMetallicRoughness": pbr });
+        let emissive = material.emissive;
+        let strength = emissive.red.max(emissive.green).max(emissive.blue);
+        if strength > 0.0 {
+            let factor = [emissive.red, emissive.green, emissive.blue].map(|c| c / strength.max(1.0));
+            json["emissiveFactor"] = serde_json::json!(factor);
+            if strength > 1.0 {
+                json["extensions"]["KHR_materials_emissive_strength"] = serde_json::json!({ "emissiveStrength": strength });
+                self.uses_emissive_strength = true;
+            }
+        }
+        if material.unlit {
+            json["extensions"]["KHR_materials_unlit"] = serde_json::json!({});
+            self.uses_unlit = true;
+        }
+        if !matches!(material.alpha_mode, AlphaMode::Opaque) {
+            json["alphaMode"] = "BLEND".into();
+        }
+        if material.cull_mode.is_none() {
+            json["doubleSided"] = true.into();
+        }
+        self.materials.push(json);
+        self.material_ids.push(id);
+        self.materials.len() - 1
+    }
+
+    /// Index of the glTF mesh drawing `mesh` (already written by `mesh`) with `material`.
+    fn with_material(&mut self, mesh: AssetId<Mesh>, material: usize) -> usize {
+        if let Some(index) = self.primitives.iter().position(|&known| known == (mesh, material)) {
+            return index;
+        }
+        let primitive = self.geometry.iter().find(|(known, _)| *known == mesh).and_then(|(_, geometry)| geometry.clone());
+        let mut primitive = primitive.unwrap_or_default();
+        primitive["material"] = material.into();
+        self.meshes.push(serde_json::json!({ "primitives": [primitive] }));
+        self.primitives.push((mesh, material));
+        self.meshes.len() - 1
+    }
+
+    /// The finished binary glTF: a 12-byte header, then the JSON chunk (space-padded) and
+    /// the BIN chunk (zero-padded), each to a multiple of 4 bytes.
+    fn into_glb(mut self) -> Vec<u8> {
+        self.buffer.resize(self.buffer.len().next_multiple_of(4), 0);
+        let extensions: Vec<&str> = [
+            (self.uses_emissive_strength, "KHR_materials_emissive_strength"),
+            (self.uses_unlit, "KHR_materials_unlit"),
+        ]
+        .into_iter()
+        .filter_map(|(used, name)| used.then_some(name))
+        .collect();
+        let mut json = serde_json::json!({
+            "asset": { "version": "2.0", "generator": env!("CARGO_PKG_NAME") },
+            "scene": 0,
+            "scenes": [{ "nodes": (0..self.nodes.len()).collect::<Vec<_>>() }],
+            "nodes": self.nodes,
+            "meshes": self.meshes,
+            "materials": self.materials,
+            "accessors": self.accessors,
+            "bufferViews": self.buffer_views,
+            "buffers": [{ "byteLength": self.buffer.len() }],
+        });
+        if !self.images.is_empty() {
+            json["textures"] = (0..self.images.len()).map(|source| serde_json::json!({ "source": source })).collect();
+            json["images"] = self.images.iter().map(|uri| serde_json::json!({ "uri": uri })).collect();
+        }
+        if !extensions.is_empty() {
+            json["extensionsUsed"] = serde_json::json!(extensions);
+        }
+        let mut json = json.to_string().into_bytes();
+        json.resize(json.len().next_multiple_of(4), b' ');
+        let total = 12 + 8 + json.len() + 8 + self.buffer.len();
+        let mut glb = Vec::with_capacity(total);
+        for word in [0x4654_6C67, 2, total as u32, json.len() as u32, 0x4E4F_534A] {
+            glb.extend_from_slice(&u32::to_le_bytes(word)); // "glTF", version 2, length; JSON chunk
+        }
+        glb.extend_from_slice(&json);
+        for word in [self.buffer.len() as u32, 0x004E_4942] {
+            glb.extend_from_slice(&u32::to_le_bytes(word)); // BIN chunk
+        }
+        glb.extend_from_slice(&self.buffer);
+        glb
+    }
+}
+
+/// System: while `record_csv` is on, appends a row of sim time + every body's world x/y/z
+/// to the CSV file at each sampling interval. Opens the file (truncating it) when logging
+/// starts, flushes once a second, and closes it when logging stops or the app exits.
+pub(crate) fn log_positions(
+    mut csv: ResMut<CsvExport>,
+    mut exit: EventReader<AppExit>,
+    bodies: Query<(Entity, &Name, &GlobalTransform), With<BodyRadius>>,
+    clock: Res<SimClock>,
+    real_time: Res<Time<Real>>,
+) {
+    if !csv.record_csv || exit.read().count() > 0 {
+        csv.close();
+        return;
+    }
+    if csv.writer.is_none() {
+        let opened = File::create(&csv.path).map(BufWriter::new).and_then(|mut writer| {
+            let mut columns: Vec<_> = bodies.iter().map(|(entity, name, _)| (entity, name.to_string())).collect();
+            columns.sort_by(|a, b| a.1.cmp(&b.1));
+            let header: String = columns.iter().map(|(_, name)| format!(",{name}_x,{name}_y,{name}_z")).collect();
+            writeln!(writer, "sim_seconds{header}")?;
+            Ok((writer, columns.into_iter().map(|(entity, _)| entity).collect()))
+        });
+        match opened {
+            Ok((writer, columns)) => {
+                info!("Logging positions to {}", csv.path);
+                csv.writer = Some(writer);
+                csv.columns = columns;
+                csv.since_sample = f32::INFINITY; // first row right away
+                csv.since_flush = 0.0;
+            }
+            Err(err) => {
+                warn!("Can't write {}: {err}", csv.path);
+                csv.record_csv = false;
+                return;
+            }
+        }
+    }
+    let dt = real_time.delta_seconds();
+    csv.since_sample += dt;
+    csv.since_flush += dt;
+    if csv.since_sample < csv.interval {
+        return;
+    }
+    csv.since_sample = 0.0;
+    let mut row = format!("{:.4}", clock.sim_seconds);
+    for &entity in &csv.columns {
+        match bodies.get(entity) {
+            Ok((_, _, global)) => {
+                let p = global.translation();
+                row.push_str(&format!(",{},{},{}", p.x, p.y, p.z));
+            }
+            Err(_) => row.push_str(",,,"),
+        }
+    }
+    let flush = csv.since_flush >= 1.0;
+    let Some(writer) = csv.writer.as_mut() else { return };
+    let result = writeln!(writer, "{row}").and_then(|()| if flush { writer.flush() } else { Ok(()) });
+    if flush {
+        csv.since_flush = 0.0;
+    }
+    if let Err(err) = result {
+        warn!("Stopped logging to {}: {err}", csv.path);
+        csv.record_csv = false;
+        csv.close();
+    }
+}
+
+/// System: services Save/Load requests from the UI. Saving snapshots every named body
+/// (those with `Spin`) and its orbit pivot; loading restores the sliders and poses.
+/// Entries that don't match the current scene are skipped with a warning. A file without
+/// `angles` (saved before they were) still loads: its rotations become the new
+/// `InitialPose`s, with the angles counting from zero.
+pub(crate) fn handle_state_file(
+    mut requests: EventReader<StateFileRequest>,
+    mut params: ResMut<SimulationParams>,
+    mut clock: ResMut<SimClock>,
+    mut bodies: Query<SaveableBody, Without<Orbit>>,
+    mut pivots: Query<(&mut Transform, &mut Orbit, Option<&mut InitialPose>)>,
+    mut trails: Query<&mut OrbitTrail>,
+) {
+    for request in requests.read() {
+        match request {
+            StateFileRequest::Save => {
+                let bodies = bodies
+                    .iter()
+                    .map(|(name, transform, parent, ellipse, spin, pose)| {
+                        let pivot = parent.and_then(|p| pivots.get(p.get()).ok());
+                        SavedBody {
+                            name: name.to_string(),
+                            translation: transform.translation.to_array(),
+                            rotation: transform.rotation.to_array(),
+                            pivot_rotation: pivot.as_ref().map(|(pivot, ..)| pivot.rotation.to_array()),
+                            elliptical: ellipse.map(|e| SavedEllipse {
+                                theta: e.theta,
+                                mean_anomaly: e.mean_anomaly,
+                            }),
+                            angles: pose.map(|pose| SavedAngles {
+                                pose: pose.0.to_array(),
+                                spin: spin.angle,
+                                axis_yaw: spin.axis_yaw,
+                                pivot: pivot.and_then(|(_, orbit, pose)| Some((pose?.0.to_array(), orbit.yaw))),
+                            }),
+                        }
+                    })
+                    .collect();
+                let state = SavedState {
+                    params: params.clone(),
+                    sim_seconds: clock.sim_seconds,
+                    earth_orbit_angle: clock.earth_orbit_angle,
+                    bodies,
+                };
+                match save_state(STATE_FILE, &state) {
+                    Ok(()) => info!("Saved simulation state to {STATE_FILE}"),
+                    Err(err) => warn!("Could not save {STATE_FILE}: {err}"),
+                }
+            }
+            StateFileRequest::Load => {
+                let state = match load_state(STATE_FILE) {
+                    Ok(state) => state,
+                    Err(err) => {
+                        warn!("Could not load {STATE_FILE}: {err}");
+                        continue;
+                    }
+                };
+                *params = state.params;
+                clock.sim_seconds = state.sim_seconds;
+                clock.earth_orbit_angle = state.earth_orbit_angle;
+                for saved in &state.bodies {
+                    let Some((_, mut transform, parent, ellipse, mut spin, pose)) =
+                        bodies.iter_mut().find(|(name, ..)| name.as_str() == saved.name)
+                    else {
+                        warn!("Saved body '{}' does not exist in this scene; skipped", saved.name);
+                        continue;
+                    };
+                    transform.translation = Vec3::from_array(saved.translation);
+                    transform.rotation = Quat::from_array(saved.rotation).normalize();
+                    let (body_pose, spin_angle, axis_yaw) = match &saved.angles {
+                        Some(angles) => (Quat::from_array(angles.pose).normalize(), angles.spin, angles.axis_yaw),
+                        None => (transform.rotation, 0.0, 0.0),
+                    };
+                    (spin.angle, spin.axis_yaw) = (spin_angle, axis_yaw);
+                    if let Some(mut pose) = pose {
+                        pose.0 = body_pose;
+                    }
+                    match (saved.pivot_rotation, parent.and_then(|p| pivots.get_mut(p.get()).ok())) {
+                        (Some(rotation), Some((mut pivot, mut orbit, pivot_pose))) => {
+                            pivot.rotation = Quat::from_array(rotation).normalize();
+                            let (pose, yaw) = match saved.angles.as_ref().and_then(|a| a.pivot) {
+                                Some((pose, yaw)) => (Quat::from_array(pose).normalize(), yaw),
+                                None => (pivot.rotation, 0.0),
+                            };
+                            orbit.yaw = yaw;
+                            if let Some(mut pivot_pose) = pivot_pose {
+                                pivot_pose.0 = pose;
+                            }
+                        }
+                        (Some(_), None) => warn!("'{}' has no orbit pivot; orbital angle not restored", saved.name),
+                        _ => {}
+                    }
+                    match (&saved.elliptical, ellipse) {
+                        (Some(saved_ellipse), Some(mut ellipse)) => {
+                            ellipse.theta = saved_ellipse.theta;
+                            ellipse.mean_anomaly = saved_ellipse.mean_anomaly;
+                        }
+                        (Some(_), None) => warn!("'{}' has no elliptical orbit; ellipse state ignored", saved.name),
+                        _ => {}
+                    }
+                }
+                // Old trail samples would draw a jump from the previous pose
+                for mut trail in &mut trails {
+                    trail.points.clear();
+                }
+                info!("Loaded simulation state from {STATE_FILE}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_mesh_glb_is_well_formed() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default())).init_asset::<Mesh>().init_asset::<StandardMaterial>();
+        let cube = Mesh::from(Cuboid::default());
+        let vertex_count = cube.count_vertices();
+        let index_count = cube.indices().map_or(0, |indices| indices.len());
+        let mesh = app.world_mut().resource_mut::<Assets<Mesh>>().add(cube);
+        let material = app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial::default());
+        let world = app.world();
+        let mut export = GltfExport::default();
+        let id = export.mesh(&mesh, world.resource::<Assets<Mesh>>()).expect("a cuboid is a triangle list");
+        let material = export.material(&material, world.resource::<Assets<StandardMaterial>>(), world.resource::<AssetServer>());
+        let index = export.with_material(id, material);
+        export.nodes.push(serde_json::json!({ "name": "Cube", "mesh": index }));
+        let glb = export.into_glb();
+
+        let word = |at: usize| u32::from_le_bytes(glb[at..at + 4].try_into().unwrap()) as usize;
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(word(4), 2, "glTF version");
+        assert_eq!(word(8), glb.len(), "header length");
+        let json_length = word(12);
+        assert_eq!(word(16), 0x4E4F_534A, "first chunk is JSON");
+        let bin_at = 20 + json_length;
+        let bin_length = word(bin_at);
+        assert_eq!(word(bin_at + 4), 0x004E_4942, "second chunk is BIN");
+        assert!(json_length % 4 == 0 && bin_length % 4 == 0, "chunks padded to 4 bytes: {json_length}, {bin_length}");
+        assert_eq!(bin_at + 8 + bin_length, glb.len(), "nothing after the BIN chunk");
+
+        let json: serde_json::Value = serde_json::from_slice(&glb[20..bin_at]).expect("JSON chunk parses");
+        assert_eq!(json["asset"]["version"], "2.0");
+        assert_eq!(json["buffers"][0]["byteLength"], bin_length);
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 1);
+        let primitive = &json["meshes"][0]["primitives"][0];
+        assert_eq!(primitive["material"], 0);
+        let accessors = json["accessors"].as_array().unwrap();
+        let views = json["bufferViews"].as_array().unwrap();
+        for name in ["POSITION", "NORMAL", "TEXCOORD_0"] {
+            let accessor = &accessors[primitive["attributes"][name].as_u64().unwrap() as usize];
+            assert_eq!(accessor["count"], vertex_count, "{name} count");
+        }
+        assert_eq!(accessors[primitive["indices"].as_u64().unwrap() as usize]["count"], index_count);
+        for accessor in accessors {
+            let components = match accessor["type"].as_str().unwrap() {
+                "SCALAR" => 1,
+                "VEC2" => 2,
+                "VEC3" => 3,
+                other => panic!("unexpected accessor type {other}"),
+            };
+            let view = &views[accessor["bufferView"].as_u64().unwrap() as usize];
+            let (offset, length) = (view["byteOffset"].as_u64().unwrap() as usize, view["byteLength"].as_u64().unwrap() as usize);
+            assert_eq!(length, accessor["count"].as_u64().unwrap() as usize * components * 4, "{accessor}: view length");
+            assert!(offset % 4 == 0 && offset + length <= bin_length, "{accessor}: view {offset}+{length} outside the buffer");
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use bevy::asset::load_internal_asset;
+use bevy::diagnostic::{EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin};
+use bevy::pbr::wireframe::WireframePlugin;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy::transform::TransformSystem;
+use bevy::window::WindowResolution;
+use bevy_egui::EguiPlugin;
+use std::time::Duration;
+
+mod bench;
+mod camera;
+mod eclipse;
+mod export;
+mod orbits;
+mod overlays;
+mod params;
+mod physics;
+mod render;
+mod scene;
+mod ui;
+mod window;
+
+use bench::*;
+use camera::*;
+use eclipse::*;
+use export::*;
+use orbits::*;
+use overlays::*;
+use params::*;
+use physics::*;
+use render::*;
+use scene::*;
+use ui::*;
+use window::*;
+
+pub use window::WindowConfig;
+
+/// Runs the app the `orbit-sun-earth-moon` binary starts: `window.ron` and the command
+/// line pick the window size and mode, or a `--headless` / `--bench` run instead.
+pub fn run() {
+    let (window_config, headless) = WindowConfig::from_file_and_args();
+    if let Some(HeadlessRun::Bench(steps)) = headless {
+        run_bench(steps);
+        return;
+    }
+    if let Some(HeadlessRun::Frames(frames)) = headless {
+        let mut app = headless_app();
+        app.add_systems(Last, move |mut frame: Local<u32>, mut exit: EventWriter<AppExit>| {
+            *frame += 1;
+            if *frame >= frames {
+                exit.send(AppExit::Success);
+            }
+        });
+        app.run();
+        return;
+    }
+    App::new()
+        .add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: "Earth orbiting the Sun (Bevy)".into(),
+                        resolution: WindowResolution::new(window_config.width, window_config.height),
+                        mode: window_config.mode(),
+                        ..default()
+                    }),
+                    ..default()
+                }),
+        )
+        .insert_resource(window_config)
+        .add_plugins((SolarSystemPlugin, SolarSystemUiPlugin))
+        .run();
+}
+
+/// The simulation without a window, renderer, input, or egui: `MinimalPlugins` plus the
+/// asset and transform plumbing `SolarSystemPlugin` needs, on a fixed 1/60 s clock so every
+/// `update()` advances the same amount. Used by `--headless`; tests can step it directly
+/// and read body `GlobalTransform`s (each body carries its `Name`).
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), TransformPlugin, HierarchyPlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<Image>() // Earth's texture handle; with no PNG loader it just fails over
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1.0 / 60.0)))
+        .add_plugins(SolarSystemPlugin);
+    app
+}
+
+/// Plugin: everything that moves the bodies — parameters, clock, events, the scene itself,
+/// and the stepping/sync systems. Needs no rendering or UI plugins, so it runs under
+/// `headless_app`; add `SolarSystemUiPlugin` as well for the interactive app.
+///
+/// The app must already provide:
+/// - the main schedules and `Time` (`MinimalPlugins` or `DefaultPlugins`); it spawns the
+///   scene in `Startup`, steps in `Update` (or `FixedUpdate` with the fixed-timestep
+///   option), and samples trails, eclipses and motion in `PostUpdate` after
+///   `TransformSystem::TransformPropagate`,
+/// - `TransformPlugin` and `HierarchyPlugin`, for the pivot → body hierarchy,
+/// - `AssetPlugin` with `Assets<Mesh>`, `Assets<StandardMaterial>` and `Assets<Image>`
+///   (Earth's texture just fails over without an image loader).
+///
+/// It inserts its own resources (`SimulationParams`, the sim clock, eclipse and overlap
+/// state) and events.
+pub struct SolarSystemPlugin;
+
+impl Plugin for SolarSystemPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // Default simulation parameters
+            .init_resource::<SimulationParams>()
+            .init_resource::<EclipseState>()
+            .init_resource::<Overlaps>()
+            .init_resource::<UnboundBodies>()
+            .init_resource::<SimClock>()
+            .init_resource::<BarycentricShift>()
+            .init_resource::<Selected>()     // cleared by `reset_simulation`
+            .init_resource::<CameraTween>()  // cancelled by `reset_simulation`
+            .init_resource::<CsvExport>()
+            .add_event::<StateFileRequest>()
+            .add_event::<ResetSimulation>()
+            .add_event::<StartPhaseChanged>()
+            .add_event::<WarpToTime>()
+            .add_event::<CircularizeOrbits>()
+            // Build the initial ECS world (entities/graph)
+            .add_systems(Startup, setup_scene)
+            // Fixed-timestep copies of the stepping systems (see `fixed_timestep`)
+            .add_systems(
+                FixedUpdate,
+                (animate_orbits, spin_bodies, animate_elliptical_orbits, precess_axes, tick_clock).run_if(fixed_timestep),
+            )
+            // Behavior systems run every frame (Update schedule)
+            .add_systems(
+                Update,
+                (
+                    (
+                        animate_orbits,        // rotate pivots for circular orbits
+                        spin_bodies,           // spin Sun/Earth/Moon
+                        animate_elliptical_orbits, // drive bodies along their ellipses if enabled
+                        precess_axes,          // optional slow drift of Earth's axis
+                        tick_clock,            // advance simulated time / Earth years
+                    )
+                        .run_if(not(fixed_timestep)), // `FixedUpdate` runs these instead
+                    (
+                        sync_ellipse_modes.before(enforce_orbit_radii), // which bodies ride ellipses
+                        apply_orbit_directions.before(warp_to_time), // prograde / retrograde
+                        apply_moon_resonance.before(warp_to_time), // resonance ratio → Moon's rate
+                        apply_planet_orbits.before(warp_to_time), // "Orbits" sliders → planets' radii and rates
+                    )
+                        .before(animate_orbits)
+                        .before(animate_elliptical_orbits),
+                    auto_slow_mo,              // ease time dilation around alignments
+                    apply_body_scales,         // artistic vs true relative body sizes
+                    texture_fallback,          // solid colors for textures that failed to load
+                    rebuild_body_meshes,       // re-tessellate spheres when mesh detail changes
+                    rebuild_asteroid_belt.before(reset_simulation), // respawn the belt when its sliders change
+                    sync_comet,                // spawn/despawn the comet with its toggle
+                    sync_moons.before(reset_simulation), // extra moons for Earth/Jupiter
+                    apply_start_phase.before(orient_moon_plane), // start-phase sliders → pivot yaw
+                    record_initial_poses.before(animate_orbits).before(spin_bodies), // t = 0 poses
+                    (
+                        warp_to_time.after(record_initial_poses), // "Go to" a sim time
+                        apply_rotations            // f64 angles → pivot and body rotations
+                            .after(animate_orbits)
+                            .after(spin_bodies)
+                            .after(precess_axes)
+                            .after(apply_start_phase)
+                            .after(handle_state_file),
+                    )
+                        .chain(),
+                    orient_moon_plane.after(apply_rotations).after(apply_body_scales), // inclined Moon orbit
+                    enforce_orbit_radii,       // apply new radii from sliders in circular mode
+                    apply_slider_ellipses.before(orient_moon_plane), // radius / eccentricity / tilt → Earth's and the Moon's ellipses
+                    apply_masses.before(nbody_gravity), // mass sliders → `Mass`
+                    nbody_gravity.after(orient_moon_plane), // physics mode: mutual gravity
+                    lock_moon_face.after(nbody_gravity).after(enforce_orbit_radii), // tidal locking
+                    handle_state_file,         // save/load buttons → RON file
+                    reset_simulation,          // reset button → defaults + fresh bodies
+                ),
+            )
+            // Offset Earth about the barycenter for this frame's propagation only
+            .add_systems(
+                PostUpdate,
+                (
+                    apply_barycentric_shift.before(TransformSystem::TransformPropagate),
+                    restore_barycentric_shift.after(TransformSystem::TransformPropagate),
+                ),
+            )
+            // Sample trails once world transforms are final for this frame
+            .add_systems(
+                PostUpdate,
+                (
+                    record_trails,             // append to trail ring buffers
+                    eclipse_detection,         // classify Sun–Earth–Moon alignment
+                    track_motion,              // world-space velocity estimates
+                    detect_overlaps,           // intersecting bodies → UI warning / radius clamp
+                    detect_unbound,            // physics mode: escaping bodies → UI warning
+                    log_positions,             // CSV export
+                )
+                    .after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Plugin: cameras, lighting, gizmo overlays, input, and the egui panels, on top of
+/// `SolarSystemPlugin`. Needs `DefaultPlugins`; adds `EguiPlugin` and the diagnostics the
+/// HUD reads unless the app already has them. Insert a `WindowConfig` first to override
+/// the defaults (as `run` does from `window.ron` and the command line).
+///
+/// Besides `SolarSystemPlugin`, it needs `DefaultPlugins` with a primary window (input,
+/// rendering, PBR, gizmos, audio, and an asset server for the optional textures under
+/// `assets/`). Its systems run in `Startup` (cameras, lights, starfield), `Update` (input,
+/// camera, panels) and `PostUpdate` (gizmo markers, screenshots, the glTF export, after
+/// transform propagation). It inserts `AmbientLight` and `ClearColor` itself, so the app shouldn't.
+pub struct SolarSystemUiPlugin;
+
+impl Plugin for SolarSystemUiPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+        app.insert_gizmo_config(XRayGizmos, GizmoConfig { depth_bias: -1.0, ..default() });
+        load_internal_asset!(app, NIGHT_LIGHTS_SHADER, "night_lights.wgsl", Shader::from_wgsl);
+        app.add_plugins(MaterialPlugin::<NightLightsMaterial>::default());
+        // Only builds its line pipeline for entities given a `Wireframe`, which
+        // `apply_wireframes` skips on adapters without line polygon mode
+        if !app.is_plugin_added::<WireframePlugin>() {
+            app.add_plugins(WireframePlugin);
+        }
+        // Feed the performance HUD
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        if !app.is_plugin_added::<EntityCountDiagnosticsPlugin>() {
+            app.add_plugins(EntityCountDiagnosticsPlugin);
+        }
+        app
+            .init_resource::<WindowConfig>()
+            // A dim ambient so the Sun's light + PBR shading stand out (then kept by `apply_lighting`)
+            .insert_resource(SimulationParams::default().ambient_light())
+            .insert_resource(SimulationParams::default().clear_color())
+            .init_resource::<CameraMode>()
+            .add_event::<ScreenshotRequest>()
+            .add_event::<ExportGltfRequest>()
+            .add_event::<CameraPresetRequest>()
+            .add_event::<FrameAllRequest>()
+            .init_resource::<ScreenshotSettings>()
+            .insert_resource(StarfieldConfig {
+                star_count: 3000,
+                seed: 0x5EED,
+                radius: 500.0,
+            })
+            .add_systems(Startup, (setup, setup_skybox, setup_ambient_hum))
+            .add_systems(PreUpdate, suspend_while_minimized) // no stepping while minimized
+            .add_systems(Update, keyboard_controls) // `KEY_BINDINGS` shortcuts
+            .add_systems(Update, update_ambient_hum) // orbit speed → hum pitch / volume
+            .add_systems(Update, sync_night_lights) // city lights on Earth's dark side
+            .add_systems(Update, apply_reference_frame.before(follow_camera)) // keep the frame body centered
+            .add_systems(Update, apply_color_scheme) // planet color scheme → their materials
+            .add_systems(Update, apply_render_scale) // render scale → main camera target + upscale
+            // Camera + overlays
+            .add_systems(
+                Update,
+                (
+                    apply_camera_preset.before(tween_camera), // preset buttons → tween goal
+                    frame_all_bodies.before(tween_camera), // "Frame all" → tween goal
+                    follow_camera.before(orbit_camera), // glide the camera target after a body
+                    tween_camera.after(follow_camera).before(orbit_camera), // ease toward a preset
+                    orbit_camera,              // mouse-driven orbit/pan/zoom camera
+                    resize_selection,          // Ctrl+scroll → selected body's `SizeScale`
+                    follow_camera_skybox.after(orbit_camera), // keep stars infinitely distant
+                    draw_trails,               // gizmo polylines through the trail buffers
+                    pick_body,                 // click a body to select it
+                    highlight_selection,       // outline gizmo around the selection
+                    apply_bloom,               // bloom slider → camera
+                    apply_msaa,                // antialiasing choice → `Msaa` (with GPU fallback)
+                    apply_wireframes.run_if(wireframes_supported), // wireframe toggles → `Wireframe` overlays
+                    apply_body_visibility,     // per-body show/hide checkboxes
+                    apply_camera_projection,   // FOV / near / far sliders → camera
+                    apply_lighting,            // ambient / directional light sliders
+                    apply_shadows,             // shadow toggle / map size → Sun light
+                    apply_background.after(apply_camera_projection), // clear color / gradient backdrop
+                    update_minimap,            // top-down inset: toggle, viewport, zoom
+                    apply_window_config,       // size / fullscreen → window (+ saved)
+                ),
+            )
+            // UI
+            .add_systems(
+                Update,
+                (
+                    ui_panel,                  // sliders UI
+                    moon_phase_panel,          // current Moon phase + painted disc
+                    draw_labels,               // body names projected to screen space
+                    selection_panel,           // info side panel for the selection
+                    hover_tooltip,             // name + Sun distance under the cursor
+                    perf_hud,                  // FPS / frame time / entity count
+                    help_window,               // controls reference (F1)
+                )
+                    .run_if(ui_visible),       // hidden for scene-only screenshots
+            )
+            // Place markers once world transforms are final for this frame
+            .add_systems(
+                PostUpdate,
+                (
+                    draw_lagrange_points,      // L1–L5 markers
+                    draw_barycenter,           // Earth–Moon center of mass
+                    pause_at_alignment,        // optional auto-pause at new/full moon
+                    draw_velocity_arrows.after(track_motion), // velocity vectors
+                    draw_comet_tail,           // anti-sunward tail gizmos
+                    draw_ecliptic_guides,      // grid + orbit circles
+                    draw_nodes,                // lines of nodes on inclined orbits
+                    draw_apsides,              // perihelion/aphelion markers
+                    debug_day_night,           // sunward tint + terminator circles
+                    draw_day_markers,          // sidereal vs solar day lines on Earth
+                    draw_retrograde_path,      // Mars's apparent path on the sky
+                )
+                    .after(TransformSystem::TransformPropagate),
+            )
+            // After all Update systems, so a UI-hiding frame has fully skipped the UI
+            .add_systems(PostUpdate, take_screenshot)
+            .add_systems(PostUpdate, export_gltf.after(TransformSystem::TransformPropagate));
+    }
+}
@@ -0,0 +1,23 @@
+// Earth's city lights, drawn additively on a shell just above the surface: the lights
+// texture, faded in across the terminator by how far the surface faces away from the Sun.
+
+#import bevy_pbr::forward_io::VertexOutput
+
+struct NightLights {
+    sun_position: vec4<f32>, // world space (w unused)
+    strength: f32,
+}
+
+@group(2) @binding(0) var<uniform> material: NightLights;
+@group(2) @binding(1) var lights_texture: texture_2d<f32>;
+@group(2) @binding(2) var lights_sampler: sampler;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let to_sun = normalize(material.sun_position.xyz - in.world_position.xyz);
+    let sun_angle = dot(normalize(in.world_normal), to_sun);
+    // Lights come on just past the terminator, fully lit a little way into the night
+    let night = 1.0 - smoothstep(-0.2, 0.05, sun_angle);
+    let lights = textureSample(lights_texture, lights_sampler, in.uv).rgb;
+    return vec4<f32>(lights * material.strength * night, 1.0);
+}
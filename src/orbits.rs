@@ -0,0 +1,1147 @@
+//! Kinematic motion: orbit pivots, spins, ellipses, and the systems that step and pose them.
+
+use bevy::prelude::*;
+use std::f32::consts::{PI, TAU};
+use crate::{eclipse::*, overlays::*, params::*, physics::*, scene::*};
+
+/// Resource: names of body pairs whose spheres intersect this frame, from `detect_overlaps`.
+#[derive(Resource, Default, PartialEq)]
+pub(crate) struct Overlaps(pub(crate) Vec<(String, String)>);
+
+/// Which orbit a start-phase slider drives.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum PhaseTarget {
+    Earth,
+    Moon,
+    Planet(usize), // index into `PLANETS`
+}
+
+/// Event: a start-phase slider moved; `apply_start_phase` puts that body back at its phase.
+#[derive(Event, Clone, Copy)]
+pub(crate) struct StartPhaseChanged(pub(crate) PhaseTarget);
+
+/// Event: jump straight to a simulated time, `years` after t = 0 (sent by the "Go to"
+/// field in `ui_panel`, handled by `warp_to_time`).
+#[derive(Event, Clone, Copy)]
+pub(crate) struct WarpToTime {
+    pub(crate) years: f64,
+}
+
+/// Component: entity whose local yaw is rotated each frame to carry children
+/// in a circular path (we attach this to *pivot* entities). The yaw accumulates in f64,
+/// wrapped to [0, 2π), and `apply_rotations` rebuilds the rotation from it, so long runs
+/// don't pile up rounding error the way repeated `rotate_y` calls would.
+#[derive(Component)]
+pub(crate) struct Orbit {
+    pub(crate) angular_speed: f32, // rad per simulated second (base), multiplied by `SimulationParams::orbit_scale`
+    pub(crate) yaw: f64,           // rad turned since the `InitialPose`
+}
+
+impl Orbit {
+    pub(crate) fn new(angular_speed: f32) -> Self {
+        Self { angular_speed, yaw: 0.0 }
+    }
+}
+
+/// Component: entity spins around its own local Y axis each frame. Like `Orbit`, the
+/// angles are f64 state that `apply_rotations` turns into the rotation.
+#[derive(Component)]
+pub(crate) struct Spin {
+    pub(crate) angular_speed: f32, // rad per simulated second (base), multiplied by SimulationParams.spin_speed_scale
+    pub(crate) angle: f64,         // rad spun since the `InitialPose`
+    pub(crate) axis_yaw: f64,      // rad the spin axis has precessed (westward) about the world vertical
+}
+
+impl Spin {
+    pub(crate) fn new(angular_speed: f32) -> Self {
+        Self { angular_speed, angle: 0.0, axis_yaw: 0.0 }
+    }
+}
+
+/// Component: world-space motion estimated from position deltas by `track_motion`.
+#[derive(Component, Default)]
+pub(crate) struct TrackedMotion {
+    pub(crate) last_position: Option<Vec3>,
+    pub(crate) velocity: Vec3, // scene units/s, zero while paused
+}
+
+/// Component: local rotation of an orbit pivot or spinning body at t = 0, recorded by
+/// `record_initial_poses` and kept in step with the start-phase sliders. `apply_rotations`
+/// rebuilds the current rotation from it and the `Orbit`/`Spin` angles every frame.
+#[derive(Component, Clone, Copy)]
+pub(crate) struct InitialPose(pub(crate) Quat);
+
+/// Component: elliptical orbit for an entity (e.g., Earth), in two flavors:
+/// - Kepler (default): advance the mean anomaly uniformly and solve Kepler's equation,
+///   so the body speeds up near perihelion (Sun at a focus).
+/// - Uniform: integrate an explicit parameter angle `theta` (not true anomaly); same
+///   focus-centered ellipse, but no speed-up near perihelion.
+#[derive(Component)]
+pub(crate) struct EllipticalOrbit {
+    pub(crate) a: f32,             // semi-major axis
+    pub(crate) b: f32,             // semi-minor axis
+    pub(crate) angular_speed: f32, // parametric speed (rad per sim s), uniform mode
+    pub(crate) theta: f32,         // current param angle (state), uniform mode
+    pub(crate) eccentricity: f32,  // 0 = circle, must stay < 1
+    pub(crate) mean_motion: f32,   // mean anomaly rate (rad per sim s) = 2π / period, Kepler mode
+    pub(crate) mean_anomaly: f32,  // current mean anomaly (state), Kepler mode
+    pub(crate) inclination: f32,   // tilt of the orbit plane about the major axis (X), rad
+    pub(crate) theta_epoch: f32,        // `theta` at t = 0, for `warp_to_time`
+    pub(crate) mean_anomaly_epoch: f32, // `mean_anomaly` at t = 0, for `warp_to_time`
+    pub(crate) active: bool,       // drives the body now (its pivot holds still); set by `sync_ellipse_modes`
+}
+
+impl EllipticalOrbit {
+    const MIN_AXIS: f32 = 0.01; // smallest semi-axis `sanitize` lets through
+    const MAX_ECCENTRICITY: f32 = 0.99; // Kepler's equation and `b` both degenerate at 1
+
+    /// Builds an orbit from semi-major axis and eccentricity; `b` is derived so both modes
+    /// trace the same ellipse shape. `speed` drives both the parametric and mean motion.
+    pub(crate) fn new(a: f32, eccentricity: f32, speed: f32) -> Self {
+        Self {
+            a,
+            b: a * (1.0 - eccentricity * eccentricity).sqrt(),
+            angular_speed: speed,
+            theta: 0.0,
+            eccentricity,
+            mean_motion: speed,
+            mean_anomaly: 0.0,
+            inclination: 0.0,
+            theta_epoch: 0.0,
+            mean_anomaly_epoch: 0.0,
+            active: false,
+        }
+    }
+
+    /// Position in the parent frame for the chosen timing model: the in-plane (x, z) point
+    /// tilted by `inclination` about the major axis, so perihelion and aphelion stay put.
+    pub(crate) fn position(&self, kepler: bool) -> Vec3 {
+        let (x, z) = if kepler { self.kepler_position() } else { self.parametric_position() }.into();
+        Quat::from_rotation_x(self.inclination) * Vec3::new(x, 0.0, z)
+    }
+
+    /// Places the body `phase` (rad) of eccentric anomaly past perihelion in both modes:
+    /// `theta` is that angle directly, and the mean anomaly follows from Kepler's equation.
+    /// This is the start phase, so it becomes the epoch too.
+    pub(crate) fn set_phase(&mut self, phase: f32) {
+        self.theta = phase.rem_euclid(TAU);
+        self.mean_anomaly = (phase - self.eccentricity * phase.sin()).rem_euclid(TAU);
+        self.theta_epoch = self.theta;
+        self.mean_anomaly_epoch = self.mean_anomaly;
+    }
+
+    /// Places the body where the ellipse crosses `direction` from the focus (parent frame),
+    /// in both modes: the true anomaly is read off with the inclination undone, then turned
+    /// into the eccentric anomaly (`theta`) and, through Kepler's equation, the mean anomaly.
+    /// Unlike `set_phase` the epoch is kept: switching modes doesn't move the start phase.
+    fn aim_at(&mut self, direction: Vec3) {
+        let e = self.eccentricity;
+        let local = Quat::from_rotation_x(-self.inclination) * direction;
+        let nu = (-local.z).atan2(local.x);
+        let ecc_anomaly = 2.0 * ((1.0 - e).sqrt() * (nu / 2.0).sin()).atan2((1.0 + e).sqrt() * (nu / 2.0).cos());
+        self.theta = ecc_anomaly.rem_euclid(TAU);
+        self.mean_anomaly = (ecc_anomaly - e * ecc_anomaly.sin()).rem_euclid(TAU);
+    }
+
+    /// Center-to-focus distance `c = √(a² − b²)`. `b` is clamped to `a` first, so a
+    /// hand-edited `b > a` can't produce a NaN.
+    pub(crate) fn focal_offset(&self) -> f32 {
+        let b = self.b.min(self.a);
+        (self.a * self.a - b * b).sqrt()
+    }
+
+    /// Focus-relative position (x, z) for the parametric angle `theta`: the centered ellipse
+    /// `(a·cos θ, −b·sin θ)` shifted by −c, so the Sun sits at a focus as in Kepler mode.
+    fn parametric_position(&self) -> Vec2 {
+        self.point_at(self.theta)
+    }
+
+    /// Focus-relative point (x, z) at eccentric anomaly `angle`: `(a·cos, −b·sin)` shifted by
+    /// −c. Growing angles carry +X toward −Z, the way the `Orbit` pivots turn.
+    fn point_at(&self, angle: f32) -> Vec2 {
+        let (sin, cos) = angle.sin_cos();
+        Vec2::new(self.a * cos - self.focal_offset(), -self.b.min(self.a) * sin)
+    }
+
+    /// `samples` points evenly spaced in eccentric anomaly around the whole ellipse, in the
+    /// parent frame (tilted like `position`), with the first repeated at the end to close it.
+    pub(crate) fn path(&self, samples: u32) -> impl Iterator<Item = Vec3> + '_ {
+        let tilt = Quat::from_rotation_x(self.inclination);
+        (0..=samples).map(move |i| {
+            let (x, z) = self.point_at(i as f32 / samples as f32 * TAU).into();
+            tilt * Vec3::new(x, 0.0, z)
+        })
+    }
+
+    /// Pulls the shape back into range: `a` at least `MIN_AXIS`, eccentricity in
+    /// [0, `MAX_ECCENTRICITY`], `b` in [`MIN_AXIS`, `a`], each NaN or infinite value replaced
+    /// by the nearest sane default, and non-finite anomalies reset to their epoch (or 0).
+    /// Returns whether anything had to change.
+    fn sanitize(&mut self) -> bool {
+        let before = (self.a, self.b, self.eccentricity, self.theta, self.mean_anomaly);
+        let finite_or = |value: f32, fallback: f32| if value.is_finite() { value } else { fallback };
+        self.a = finite_or(self.a, 1.0).max(Self::MIN_AXIS);
+        self.eccentricity = finite_or(self.eccentricity, 0.0).clamp(0.0, Self::MAX_ECCENTRICITY);
+        let derived = self.a * (1.0 - self.eccentricity * self.eccentricity).sqrt();
+        self.b = finite_or(self.b, derived).clamp(Self::MIN_AXIS.min(self.a), self.a);
+        self.theta = finite_or(self.theta, finite_or(self.theta_epoch, 0.0));
+        self.mean_anomaly = finite_or(self.mean_anomaly, finite_or(self.mean_anomaly_epoch, 0.0));
+        // Bitwise, so a NaN that was replaced counts as a change
+        let after = (self.a, self.b, self.eccentricity, self.theta, self.mean_anomaly);
+        let bits = |(a, b, e, t, m): (f32, f32, f32, f32, f32)| [a, b, e, t, m].map(f32::to_bits);
+        bits(before) != bits(after)
+    }
+
+    /// (perihelion, aphelion) distances from the focus: a − c and a + c.
+    pub(crate) fn apsides(&self) -> (f32, f32) {
+        let c = self.focal_offset();
+        (self.a - c, self.a + c)
+    }
+
+    /// Focus-relative position in the orbital plane (x, z) for the current mean anomaly,
+    /// turning the same way as `point_at`.
+    fn kepler_position(&self) -> Vec2 {
+        let e = self.eccentricity;
+        let ecc_anomaly = solve_kepler(self.mean_anomaly, e);
+        // True anomaly and radius from the eccentric anomaly
+        let nu = 2.0 * ((1.0 + e).sqrt() * (ecc_anomaly / 2.0).sin())
+            .atan2((1.0 - e).sqrt() * (ecc_anomaly / 2.0).cos());
+        let r = self.a * (1.0 - e * ecc_anomaly.cos());
+        Vec2::new(r * nu.cos(), -r * nu.sin())
+    }
+}
+
+/// Solves Kepler's equation `M = E - e·sin(E)` for the eccentric anomaly `E` by Newton iteration.
+/// Starting from `E = π` for high eccentricity keeps the iteration stable near perihelion.
+fn solve_kepler(mean_anomaly: f32, e: f32) -> f32 {
+    let mut ecc_anomaly = if e > 0.8 { PI } else { mean_anomaly };
+    for _ in 0..16 {
+        let f = ecc_anomaly - e * ecc_anomaly.sin() - mean_anomaly;
+        let step = f / (1.0 - e * ecc_anomaly.cos());
+        ecc_anomaly -= step;
+        if step.abs() < 1e-6 {
+            break;
+        }
+    }
+    ecc_anomaly
+}
+
+/// Query data for a body `detect_overlaps` checks.
+type OverlapBody = (&'static Name, &'static GlobalTransform, &'static BodyRadius, Has<Sun>, Has<Earth>, Has<Moon>);
+
+/// System: lists bodies whose spheres intersect (centers closer than the sum of their
+/// scaled radii) for the warning under the distance sliders. With `clamp_radii` it also
+/// holds those sliders at their minimum safe separations: the Moon's orbit outside Earth's
+/// surface plus the Moon's radius, and Earth's far enough out that the whole Moon orbit
+/// clears the Sun (at perihelion, on the ellipse).
+pub(crate) fn detect_overlaps(bodies: Query<OverlapBody>, mut overlaps: ResMut<Overlaps>, mut params: ResMut<SimulationParams>) {
+    let mut spheres = Vec::new();
+    let (mut sun, mut earth, mut moon) = (None, None, None);
+    for (name, global, radius, is_sun, is_earth, is_moon) in &bodies {
+        let (scale, _, center) = global.to_scale_rotation_translation();
+        let radius = radius.0 * scale.max_element();
+        for (flag, slot) in [(is_sun, &mut sun), (is_earth, &mut earth), (is_moon, &mut moon)] {
+            if flag {
+                *slot = Some(radius);
+            }
+        }
+        spheres.push((name, center, radius));
+    }
+    let mut pairs = Vec::new();
+    for (i, &(a, a_center, a_radius)) in spheres.iter().enumerate() {
+        for &(b, b_center, b_radius) in &spheres[i + 1..] {
+            if a_center.distance(b_center) < a_radius + b_radius {
+                pairs.push((a.to_string(), b.to_string()));
+            }
+        }
+    }
+    overlaps.set_if_neq(Overlaps(pairs));
+
+    if !params.clamp_radii {
+        return;
+    }
+    let (Some(sun), Some(earth), Some(moon)) = (sun, earth, moon) else { return };
+    // On an ellipse the closest approach is the periapsis, a·(1 − e), and the farthest a·(1 + e)
+    let (perigee, apogee) = if params.moon_elliptical { (1.0 - params.moon_eccentricity, 1.0 + params.moon_eccentricity) } else { (1.0, 1.0) };
+    let min_moon = ((earth + moon) / perigee).min(*MOON_ORBIT_RADIUS_RANGE.end());
+    if params.moon_orbit_radius < min_moon {
+        params.moon_orbit_radius = min_moon;
+    }
+    let perihelion = if params.use_elliptical_orbit { 1.0 - params.earth_eccentricity } else { 1.0 };
+    let min_earth = ((sun + params.moon_orbit_radius * apogee + moon) / perihelion).min(*EARTH_ORBIT_RADIUS_RANGE.end());
+    if params.earth_orbit_radius < min_earth {
+        params.earth_orbit_radius = min_earth;
+    }
+}
+
+/// Query data for an `Orbit` pivot driven by `animate_orbits`.
+type OrbitPivot = (&'static mut Orbit, Option<&'static Children>, Has<MoonPivot>);
+
+/// Whether a pivot is holding still because the body it carries is riding an active ellipse.
+fn holds_for_ellipse(children: Option<&Children>, ellipses: &Query<&EllipticalOrbit>) -> bool {
+    children.into_iter().flatten().any(|&child| ellipses.get(child).is_ok_and(|e| e.active))
+}
+
+/// One step of an orbit or spin angle: `angle + rate·dt`, wrapped into [0, 2π). In f64 and
+/// reduced every step, so the angle keeps its precision however long the run.
+fn advance_angle(angle: f64, rate: f64, dt: f64) -> f64 {
+    (angle + rate * dt).rem_euclid(std::f64::consts::TAU)
+}
+
+/// System: advances the yaw of any entity with `Orbit` about its local Y axis, so a pivot
+/// placed in a tilted frame (e.g. the Moon's inclined plane) sweeps an inclined orbit.
+/// In this scene, these are *pivot* entities; children inherit the motion via hierarchy.
+/// A pivot whose body is on its ellipse holds still, so the ellipse stays fixed in space.
+/// `apply_rotations` turns the yaw into the pivot's rotation.
+pub(crate) fn animate_orbits(
+    mut q: Query<OrbitPivot>,
+    ellipses: Query<&EllipticalOrbit>,
+    time: Res<Time>,
+    clock: Res<SimClock>,
+    params: Res<SimulationParams>,
+) {
+    if params.paused || params.physics_mode {
+        return;
+    }
+    let dt = params.sim_dt(&time, &clock);
+    for (mut orbit, children, is_moon_pivot) in &mut q {
+        if holds_for_ellipse(children, &ellipses) {
+            continue;
+        }
+        let rate = f64::from(orbit.angular_speed * params.orbit_scale(is_moon_pivot));
+        orbit.yaw = advance_angle(orbit.yaw, rate, dt);
+    }
+}
+
+/// Query data for a spinning body `apply_rotations` poses.
+type PosedBody = (&'static Spin, &'static InitialPose, &'static mut Transform, Option<&'static Parent>, Has<InertialAxis>, Has<Moon>);
+
+/// System: sets every pivot's and spinning body's rotation from its `InitialPose` and the
+/// f64 angles, rather than stacking small increments: `pose · Ry(yaw)` for a pivot, and
+/// `Ry(−pivot yaw) · Ry(−axis yaw) · pose · Ry(spin)` for a body, the first factor only for
+/// an `InertialAxis` body (cancelling its pivot's yaw so the tilt keeps its world
+/// direction) and the second only for Earth's precession. Angles stay in [0, 2π), so the
+/// rotations are as exact after a million steps as after one. Entities without an
+/// `InitialPose` yet (their first frame) keep their spawn rotation; a tidally locked Moon is
+/// left to `lock_moon_face`.
+pub(crate) fn apply_rotations(
+    mut pivots: Query<(&Orbit, &InitialPose, &mut Transform)>,
+    mut bodies: Query<PosedBody, Without<Orbit>>,
+    params: Res<SimulationParams>,
+) {
+    let yaw = |angle: f64| Quat::from_rotation_y(angle as f32);
+    for (spin, pose, mut transform, parent, inertial, is_moon) in &mut bodies {
+        if is_moon && params.tidally_locked {
+            continue;
+        }
+        let parent_yaw = parent.filter(|_| inertial).and_then(|p| pivots.get(p.get()).ok()).map_or(0.0, |(orbit, ..)| orbit.yaw);
+        let rotation = yaw(-parent_yaw) * yaw(-spin.axis_yaw) * pose.0 * yaw(spin.angle);
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+    for (orbit, pose, mut transform) in &mut pivots {
+        let rotation = pose.0 * yaw(orbit.yaw);
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+/// Query data for a body under an orbit pivot, as re-phased by `apply_start_phase`.
+type PhasedBody = (
+    &'static mut Transform,
+    Option<&'static Planet>,
+    Option<&'static mut EllipticalOrbit>,
+    Has<Earth>,
+    Has<Moon>,
+    Has<InertialAxis>,
+    Option<&'static mut InitialPose>,
+);
+
+/// System: when a start-phase slider moves, puts that body back at its new phase, the way
+/// `spawn_bodies` places it: the pivot's yaw in circular mode, or the ellipse parameter
+/// (with the pivot unyawed) for a body on its ellipse. `InertialAxis` tilts get the opposite
+/// yaw change. The body jumps there even while paused, for lining up screenshots. The
+/// start phase is the t = 0 angle, so it goes into the `InitialPose`s and the pivot's
+/// accumulated yaw restarts from zero; `apply_rotations` then rebuilds both rotations.
+pub(crate) fn apply_start_phase(
+    mut events: EventReader<StartPhaseChanged>,
+    mut pivots: Query<(&mut Orbit, &Children, &mut InitialPose)>,
+    mut bodies: Query<PhasedBody, Without<Orbit>>,
+    params: Res<SimulationParams>,
+) {
+    for &StartPhaseChanged(target) in events.read() {
+        let phase = params.start_phase(target);
+        for (mut orbit, children, mut pivot_pose) in &mut pivots {
+            for &child in children {
+                let Ok((mut body, planet, ellipse, is_earth, is_moon, inertial, body_pose)) = bodies.get_mut(child) else { continue };
+                let this = match planet {
+                    _ if is_earth => PhaseTarget::Earth,
+                    _ if is_moon => PhaseTarget::Moon,
+                    Some(planet) => match PLANETS.iter().position(|p| p.name == planet.name) {
+                        Some(index) => PhaseTarget::Planet(index),
+                        None => continue,
+                    },
+                    None => continue, // asteroids, extra moons
+                };
+                if this != target {
+                    continue;
+                }
+                let mut yaw = phase;
+                if let Some(mut ellipse) = ellipse.filter(|e| e.active) {
+                    ellipse.set_phase(phase);
+                    body.translation = ellipse.position(params.use_kepler_motion);
+                    yaw = 0.0;
+                }
+                let rotation = Quat::from_rotation_y(yaw);
+                if let (true, Some(mut body_pose)) = (inertial, body_pose) {
+                    body_pose.0 = rotation.inverse() * pivot_pose.0 * body_pose.0;
+                }
+                pivot_pose.0 = rotation;
+                orbit.yaw = 0.0;
+            }
+        }
+    }
+}
+
+/// Query filter for orbit pivots and spinning bodies that have no `InitialPose` yet.
+type Unposed = (Or<(With<Orbit>, With<Spin>)>, Without<InitialPose>);
+
+/// System: records the t = 0 pose of each new orbit pivot and spinning body for
+/// `warp_to_time`. Anything spawned mid-run (a rebuilt belt, extra moons) counts its spawn
+/// pose as its t = 0 pose.
+pub(crate) fn record_initial_poses(
+    mut commands: Commands,
+    q: Query<(Entity, &Transform), Unposed>,
+) {
+    for (entity, transform) in &q {
+        // `try_insert`: a reset may despawn the entity before the command applies
+        commands.entity(entity).try_insert(InitialPose(transform.rotation));
+    }
+}
+
+/// Query data for an orbit pivot `warp_to_time` re-yaws.
+type WarpedPivot = (Entity, &'static mut Orbit, Has<EarthPivot>, Has<MoonPivot>);
+
+/// Query data for a body `warp_to_time` re-poses: spinning bodies and ellipse riders.
+type WarpedBody = (
+    &'static mut Transform,
+    Option<&'static Parent>,
+    Option<&'static mut Spin>,
+    Option<&'static mut EllipticalOrbit>,
+    Has<Earth>,
+    Has<Moon>,
+    Has<Comet>,
+);
+
+/// System: "Go to" a simulated time: sets every orbit, spin and ellipse angle straight
+/// from the time Earth takes to run `years` orbits at the current speeds, so a time always
+/// maps to the same scene. Ignored in physics mode, which has no closed form.
+pub(crate) fn warp_to_time(
+    mut events: EventReader<WarpToTime>,
+    mut clock: ResMut<SimClock>,
+    mut pivots: Query<WarpedPivot>,
+    mut bodies: Query<WarpedBody, Without<Orbit>>,
+    mut trails: Query<&mut OrbitTrail>,
+    mut motions: Query<&mut TrackedMotion>,
+    params: Res<SimulationParams>,
+) {
+    let Some(&WarpToTime { years }) = events.read().last() else { return };
+    if params.physics_mode {
+        warn!("Go to time is unavailable in physics mode");
+        return;
+    }
+    let Some(earth_rate) = pivots.iter().find(|p| p.2).map(|p| f64::from(p.1.angular_speed.abs() * params.orbit_scale(false))) else { return };
+    if earth_rate == 0.0 {
+        warn!("Can't go to a time while Earth's orbit speed is zero");
+        return;
+    }
+    // Reduced in f64, as `t` can be huge
+    let t = years * std::f64::consts::TAU / earth_rate;
+    let angle = |rate: f32| (f64::from(rate) * t).rem_euclid(std::f64::consts::TAU);
+    let mut held = Vec::new(); // pivots of bodies on their ellipses hold still, as in `animate_orbits`
+
+    for (mut transform, parent, spin, ellipse, is_earth, is_moon, is_comet) in &mut bodies {
+        if let Some(mut e) = ellipse.filter(|e| e.active) {
+            let scale = params.orbit_scale(is_moon);
+            e.mean_anomaly = (e.mean_anomaly_epoch + angle(e.mean_motion * scale) as f32).rem_euclid(TAU);
+            e.theta = (e.theta_epoch + angle(e.angular_speed * scale) as f32).rem_euclid(TAU);
+            transform.translation = e.position(params.use_kepler_motion || is_comet);
+            held.extend(parent.map(Parent::get));
+        }
+        let Some(mut spin) = spin else { continue };
+        spin.angle = angle(spin.angular_speed * params.spin_speed_scale);
+        spin.axis_yaw = if is_earth && params.axial_precession {
+            (f64::from(params.precession_deg_per_year.to_radians()) * years).rem_euclid(std::f64::consts::TAU)
+        } else {
+            0.0
+        };
+    }
+    for (entity, mut orbit, _, is_moon_pivot) in &mut pivots {
+        if !held.contains(&entity) {
+            orbit.yaw = angle(orbit.angular_speed * params.orbit_scale(is_moon_pivot));
+        }
+    }
+
+    clock.sim_seconds = f64::from(params.orbit_speed_scale) * t;
+    clock.earth_orbit_angle = years * std::f64::consts::TAU;
+    // Old trail samples would draw a jump, and velocities would spike for a frame
+    for mut trail in &mut trails {
+        trail.points.clear();
+    }
+    for mut motion in &mut motions {
+        motion.last_position = None;
+    }
+    info!("Went to {years:.3} simulated years");
+}
+
+/// Query filters for the frames `orient_moon_plane` levels, and for everything else.
+type OrbitFrame = Or<(With<MoonOrbitPlane>, With<SatelliteFrame>)>;
+type NotOrbitFrame = (Without<MoonOrbitPlane>, Without<SatelliteFrame>);
+
+/// System: orients the Moon's orbit plane in world space: undo Earth's accumulated world
+/// rotation, then tilt by the inclination. Earth's scale is undone too, so resizing Earth
+/// doesn't resize the Moon or its orbit. Runs after orbits/spins/scales so there's no lag.
+/// `SatelliteFrame`s get the same treatment, minus the tilt.
+pub(crate) fn orient_moon_plane(
+    mut plane_q: Query<(&Parent, &mut Transform, Has<MoonOrbitPlane>), OrbitFrame>,
+    ancestors: Query<(&Transform, Option<&Parent>), NotOrbitFrame>,
+    params: Res<SimulationParams>,
+) {
+    for (parent, mut transform, is_moon_plane) in &mut plane_q {
+        // Compose local rotations/scales up the hierarchy = the parent's world rotation/scale
+        let mut world_rotation = Quat::IDENTITY;
+        let mut world_scale = Vec3::ONE;
+        let mut next = Some(parent.get());
+        while let Some((ancestor, up)) = next.and_then(|e| ancestors.get(e).ok()) {
+            world_rotation = ancestor.rotation * world_rotation;
+            world_scale *= ancestor.scale;
+            next = up.map(Parent::get);
+        }
+        let tilt = if is_moon_plane { Quat::from_rotation_x(params.moon_inclination) } else { Quat::IDENTITY };
+        transform.rotation = world_rotation.inverse() * tilt;
+        transform.scale = world_scale.recip();
+    }
+}
+
+/// System: advances the spin angle of entities with `Spin` (about their local Y axis;
+/// `apply_rotations` applies it). Independent from orbital motion; purely visual for
+/// Sun/Earth/Moon. A tidally locked Moon is skipped: `lock_moon_face` orients it instead.
+pub(crate) fn spin_bodies(
+    mut q: Query<(&mut Spin, Has<Moon>)>,
+    time: Res<Time>,
+    clock: Res<SimClock>,
+    params: Res<SimulationParams>,
+) {
+    if params.paused {
+        return;
+    }
+    let dt = params.sim_dt(&time, &clock);
+    for (mut spin, is_moon) in &mut q {
+        if is_moon && params.tidally_locked {
+            continue;
+        }
+        let rate = f64::from(spin.angular_speed * params.spin_speed_scale);
+        spin.angle = advance_angle(spin.angle, rate, dt);
+    }
+}
+
+/// System: axial precession. `InertialAxis` keeps Earth's tilt fixed in world space through
+/// each orbit; this adds a deliberate slow drift on top, turning the axis about the world
+/// vertical at `precession_deg_per_year` per simulated year so it sweeps a cone over a long
+/// run. Earth's parent pivot only ever yaws, so `apply_rotations` pre-multiplying its local
+/// rotation by `axis_yaw` is a world-space yaw. Retrograde (westward), like the real
+/// precession of the equinoxes.
+pub(crate) fn precess_axes(
+    mut q: Query<&mut Spin, With<Earth>>,
+    earth_pivot_q: Query<&Orbit, With<EarthPivot>>,
+    time: Res<Time>,
+    clock: Res<SimClock>,
+    params: Res<SimulationParams>,
+) {
+    if params.paused || !params.axial_precession {
+        return;
+    }
+    let Ok(orbit) = earth_pivot_q.get_single() else { return };
+    let years = f64::from(orbit.angular_speed.abs() * params.orbit_scale(false)) * params.sim_dt(&time, &clock) / std::f64::consts::TAU;
+    let turn = f64::from(params.precession_deg_per_year.to_radians()) * years;
+    for mut spin in &mut q {
+        spin.axis_yaw = (spin.axis_yaw + turn).rem_euclid(std::f64::consts::TAU);
+    }
+}
+
+/// System: tidal locking. Earth sits at the Moon's pivot origin, so pointing the Moon's
+/// local -X (its `NearSideMarker`) back along its translation keeps that face on Earth.
+/// With the pivot carrying the Moon round this is a fixed rotation (one spin per orbit,
+/// whatever the orbit speed); in physics mode it tracks the integrated position.
+pub(crate) fn lock_moon_face(mut q: Query<&mut Transform, With<Moon>>, params: Res<SimulationParams>) {
+    if !params.tidally_locked {
+        return;
+    }
+    for mut transform in &mut q {
+        let Some(to_earth) = (-transform.translation).try_normalize() else { continue };
+        let rotation = Quat::from_rotation_arc(Vec3::NEG_X, to_earth);
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+/// Query data for a body `animate_elliptical_orbits` moves along its ellipse.
+type EllipseBody = (Entity, &'static Name, &'static mut Transform, &'static mut EllipticalOrbit, Has<Comet>, Has<Moon>);
+
+/// System: drives `EllipticalOrbit` bodies by directly setting their translation, each
+/// with its own shape and phase. Only active ellipses move (see `sync_ellipse_modes`);
+/// their pivots are held still meanwhile.
+/// Degenerate shapes (`b > a`, zero or NaN axes, eccentricity ≥ 1) are clamped back into
+/// range by `EllipticalOrbit::sanitize`, and a position that still comes out non-finite
+/// puts the body at perihelion instead, so a NaN never reaches the transform. Either
+/// is logged once per body until its orbit is valid again.
+pub(crate) fn animate_elliptical_orbits(
+    mut q: Query<EllipseBody>,
+    time: Res<Time>,
+    clock: Res<SimClock>,
+    params: Res<SimulationParams>,
+    mut flagged: Local<Vec<Entity>>, // bodies already warned about
+) {
+    if params.paused || params.physics_mode {
+        return;
+    }
+    let dt = params.sim_dt(&time, &clock) as f32;
+    for (entity, name, mut transform, mut e, is_comet, is_moon) in &mut q {
+        if !e.active {
+            continue;
+        }
+        let mut warn_once = |problem: &str| {
+            if !flagged.contains(&entity) {
+                flagged.push(entity);
+                warn!("{name}'s elliptical orbit {problem}; recovering");
+            }
+        };
+        let fixed = e.sanitize();
+        if fixed {
+            warn_once("had invalid parameters (clamped a, b and eccentricity into range)");
+        }
+        // Wrap into [0, 2π) so long (possibly reversed) runs don't lose precision
+        let kepler = params.use_kepler_motion || is_comet;
+        let scale = params.orbit_scale(is_moon);
+        if kepler {
+            e.mean_anomaly = (e.mean_anomaly + e.mean_motion * scale * dt).rem_euclid(TAU);
+        } else {
+            e.theta = (e.theta + e.angular_speed * scale * dt).rem_euclid(TAU);
+        }
+        let mut position = e.position(kepler);
+        if !position.is_finite() {
+            warn_once("produced a non-finite position (resetting to perihelion)");
+            (e.theta, e.mean_anomaly) = (0.0, 0.0);
+            position = Vec3::X * e.apsides().0;
+        } else if !fixed {
+            flagged.retain(|&flagged| flagged != entity);
+        }
+        transform.translation = position;
+    }
+}
+
+/// Query data for an ellipse rider, as switched on and off by `sync_ellipse_modes`.
+type EllipseRider = (
+    &'static mut EllipticalOrbit,
+    &'static mut Transform,
+    Option<&'static Parent>,
+    Option<&'static Planet>,
+    Has<Earth>,
+    Has<Moon>,
+    Has<Comet>,
+);
+
+/// System: decides which `EllipticalOrbit`s drive their bodies: Earth's follows
+/// `use_elliptical_orbit`, the Moon's `moon_elliptical`, each planet's its
+/// `elliptical_planets` box, and the comet's is always on. Switching keeps the body's
+/// direction from its primary, so it doesn't jump around the orbit: onto the ellipse, the
+/// pivot is unyawed and the ellipse aimed along that direction; back to circular, the
+/// pivot is yawed to it. An `InertialAxis` cancels the pivot's yaw, so the tilt doesn't
+/// move either. Only the distance changes, from the ellipse to the circle radius, which
+/// `enforce_orbit_radii` glides (or snaps) to.
+pub(crate) fn sync_ellipse_modes(mut q: Query<EllipseRider>, mut pivots: Query<&mut Orbit>, params: Res<SimulationParams>) {
+    if !params.is_changed() {
+        return;
+    }
+    for (mut ellipse, mut transform, parent, planet, is_earth, is_moon, is_comet) in &mut q {
+        let active = match planet.and_then(|planet| PLANETS.iter().position(|p| p.name == planet.name)) {
+            _ if is_comet => true,
+            _ if is_earth => params.use_elliptical_orbit,
+            _ if is_moon => params.moon_elliptical,
+            Some(index) => params.elliptical_planets[index],
+            None => false,
+        };
+        if ellipse.active == active {
+            continue;
+        }
+        ellipse.active = active;
+        let pivot = parent.and_then(|parent| pivots.get_mut(parent.get()).ok());
+        let Some(mut orbit) = pivot.filter(|_| !params.physics_mode) else { continue };
+        // In the unyawed pivot frame, where the ellipse lives
+        let direction = Quat::from_rotation_y(orbit.yaw as f32) * transform.translation;
+        if active {
+            orbit.yaw = 0.0;
+            ellipse.aim_at(direction);
+            transform.translation = ellipse.position(params.use_kepler_motion);
+        } else {
+            let yaw = (-direction.z).atan2(direction.x);
+            orbit.yaw = f64::from(yaw.rem_euclid(TAU));
+            transform.translation = Quat::from_rotation_y(-yaw) * direction;
+        }
+    }
+}
+
+/// Query filter for the bodies whose ellipse comes from the sliders.
+type SliderEllipse = Or<(With<Earth>, With<Moon>)>;
+
+/// System: copies the radius, eccentricity, and inclination sliders onto Earth's and the
+/// Moon's `EllipticalOrbit`s (`a` = the orbit radius, `b` = a·√(1 − e²), the Sun or Earth
+/// staying at a focus), and moves the body onto its new ellipse straight away when it is
+/// riding it (so it also updates when paused). The orbit's phase and period are kept. The
+/// Moon's ellipse stays flat: its orbit plane already carries the inclination.
+pub(crate) fn apply_slider_ellipses(mut q: Query<(&mut EllipticalOrbit, &mut Transform, Has<Moon>), SliderEllipse>, params: Res<SimulationParams>) {
+    if !params.is_changed() {
+        return;
+    }
+    for (mut ellipse, mut transform, is_moon) in &mut q {
+        let (a, e, inclination, riding) = if is_moon {
+            (params.moon_orbit_radius, params.moon_eccentricity, 0.0, params.moon_elliptical)
+        } else {
+            let inclination = params.earth_orbit_inclination_deg.to_radians();
+            (params.earth_orbit_radius, params.earth_eccentricity, inclination, params.use_elliptical_orbit)
+        };
+        if (ellipse.a, ellipse.eccentricity, ellipse.inclination) == (a, e, inclination) {
+            continue;
+        }
+        ellipse.a = a;
+        ellipse.b = a * (1.0 - e * e).sqrt();
+        ellipse.eccentricity = e;
+        ellipse.inclination = inclination;
+        if riding && !params.physics_mode {
+            transform.translation = ellipse.position(params.use_kepler_motion);
+        }
+    }
+}
+
+/// Query data for a planet `enforce_orbit_radii` holds at its "Orbits" radius.
+type RadiusPlanet = (&'static mut Transform, &'static Planet, &'static EllipticalOrbit);
+
+/// System: keeps Earth, the Moon, and the planets at the slider radii, as local
+/// translations along their pivots' +X. With `smooth_radius_changes` each glides toward
+/// its latest target (an exponential ease, so dragging a slider just keeps retargeting);
+/// otherwise it snaps when the params change. Each is left alone while on its ellipse
+/// (driven by `animate_elliptical_orbits`), and all are in physics mode.
+pub(crate) fn enforce_orbit_radii(
+    params: Res<SimulationParams>,
+    time: Res<Time>,
+    mut earth_q: Query<&mut Transform, (With<Earth>, Without<Moon>)>,
+    mut moon_q: Query<&mut Transform, (With<Moon>, Without<Earth>)>,
+    mut planets: Query<RadiusPlanet, (Without<Earth>, Without<Moon>)>,
+) {
+    const GLIDE_TIME: f32 = 0.25; // s to close ~63% of the gap
+    if params.physics_mode {
+        return;
+    }
+    let blend = 1.0 - (-time.delta_seconds() / GLIDE_TIME).exp();
+    let settle = |t: &mut Mut<Transform>, radius: f32| {
+        let target = Vec3::new(radius, 0.0, 0.0);
+        if !params.smooth_radius_changes {
+            if params.is_changed() {
+                t.translation = target;
+            }
+        } else if t.translation.distance_squared(target) > 1e-8 {
+            t.translation = t.translation.lerp(target, blend);
+        } else if t.translation != target {
+            t.translation = target;
+        }
+    };
+    if !params.use_elliptical_orbit {
+        if let Ok(mut t) = earth_q.get_single_mut() {
+            settle(&mut t, params.earth_orbit_radius);
+        }
+    }
+    if !params.moon_elliptical {
+        if let Ok(mut t) = moon_q.get_single_mut() {
+            settle(&mut t, params.moon_orbit_radius);
+        }
+    }
+    for (mut t, planet, _) in planets.iter_mut().filter(|(_, _, e)| !e.active) {
+        if let Some(index) = PLANETS.iter().position(|p| p.name == planet.name) {
+            settle(&mut t, params.planet_orbit_radii[index]);
+        }
+    }
+}
+
+/// System: points each body's orbit the way `retrograde_bodies` says, by the sign of its
+/// pivot's `Orbit::angular_speed` and of its ellipse's rates. The sign is set from the
+/// magnitude rather than flipped, so reapplying never double-negates, and it multiplies
+/// with the speed sliders like any base rate: a negative "Orbit speed ×" (rewind) runs a
+/// retrograde body forward again. The clock counts Earth's years from the magnitude, so
+/// time still runs forward for a retrograde Earth. Runs when the params change or new
+/// pivots appear (a reset, rebuilt moons).
+pub(crate) fn apply_orbit_directions(
+    mut pivots: Query<(&mut Orbit, &Children)>,
+    mut ellipses: Query<(&Name, &mut EllipticalOrbit)>,
+    names: Query<&Name>,
+    params: Res<SimulationParams>,
+) {
+    if !params.is_changed() && !pivots.iter_mut().any(|(orbit, _)| orbit.is_added()) {
+        return;
+    }
+    let direction = |name: &Name| if params.retrograde_bodies.iter().any(|r| r == name.as_str()) { -1.0 } else { 1.0 };
+    for (mut orbit, children) in &mut pivots {
+        let Some(name) = children.first().and_then(|&body| names.get(body).ok()) else { continue };
+        let speed = orbit.angular_speed.abs() * direction(name);
+        if orbit.angular_speed != speed {
+            orbit.angular_speed = speed;
+        }
+    }
+    for (name, mut ellipse) in &mut ellipses {
+        let sign = direction(name);
+        let (mean_motion, angular_speed) = (ellipse.mean_motion.abs() * sign, ellipse.angular_speed.abs() * sign);
+        if (ellipse.mean_motion, ellipse.angular_speed) != (mean_motion, angular_speed) {
+            (ellipse.mean_motion, ellipse.angular_speed) = (mean_motion, angular_speed);
+        }
+    }
+}
+
+/// System: sets the Moon's base orbit rate, on its pivot and its ellipse. In resonance mode
+/// it is the `resonance_target`'s base rate × Moon orbits / Earth periods (1:12 against the
+/// year = twelve orbits a year), following live edits of Earth's spin; otherwise the real
+/// sidereal month. Only the magnitude is set, so a retrograde Moon stays retrograde. The
+/// speed sliders multiply on top as usual, so the ratio holds exactly while "Planet
+/// orbits ×", "Moon orbit ×" (and, against the day, "Spin speed ×") are equal.
+pub(crate) fn apply_moon_resonance(
+    mut moon_q: Query<(&Parent, Option<&mut EllipticalOrbit>), With<Moon>>,
+    mut pivots: Query<&mut Orbit, Without<EarthPivot>>,
+    earth_pivot_q: Query<&Orbit, With<EarthPivot>>,
+    earth_q: Query<&Spin, With<Earth>>,
+    params: Res<SimulationParams>,
+) {
+    let rate = if params.moon_resonance {
+        let target = match params.resonance_target {
+            ResonanceTarget::EarthOrbit => earth_pivot_q.get_single().map(|orbit| orbit.angular_speed),
+            ResonanceTarget::EarthRotation => earth_q.get_single().map(|spin| spin.angular_speed),
+        };
+        let Ok(target) = target else { return };
+        let [periods, orbits] = params.resonance_ratio.map(|n| n.max(1) as f32);
+        target.abs() * orbits / periods
+    } else {
+        rate_for_period(MOON_MONTH_DAYS)
+    };
+    let Ok((parent, ellipse)) = moon_q.get_single_mut() else { return };
+    if let Ok(mut orbit) = pivots.get_mut(parent.get()) {
+        let speed = rate * orbit.angular_speed.signum();
+        if orbit.angular_speed != speed {
+            orbit.angular_speed = speed;
+        }
+    }
+    if let Some(mut ellipse) = ellipse {
+        let (mean_motion, angular_speed) = (rate * ellipse.mean_motion.signum(), rate * ellipse.angular_speed.signum());
+        if (ellipse.mean_motion, ellipse.angular_speed) != (mean_motion, angular_speed) {
+            (ellipse.mean_motion, ellipse.angular_speed) = (mean_motion, angular_speed);
+        }
+    }
+}
+
+/// System: copies the "Orbits" sliders onto each planet: its speed onto the pivot's
+/// `Orbit` and its ellipse's rates (keeping their signs, which `apply_orbit_directions`
+/// owns), and its radius onto the ellipse's `a` (`b` following from the eccentricity). A
+/// planet riding its ellipse moves onto the new one straight away, like
+/// `apply_slider_ellipses`; a circular one is glided out by `enforce_orbit_radii`.
+pub(crate) fn apply_planet_orbits(
+    mut planets: Query<(&Planet, &Parent, &mut EllipticalOrbit, &mut Transform)>,
+    mut pivots: Query<&mut Orbit>,
+    params: Res<SimulationParams>,
+) {
+    if !params.is_changed() {
+        return;
+    }
+    for (planet, parent, mut ellipse, mut transform) in &mut planets {
+        let Some(index) = PLANETS.iter().position(|p| p.name == planet.name) else { continue };
+        let (radius, rate) = (params.planet_orbit_radii[index], params.planet_rate(index));
+        if let Ok(mut orbit) = pivots.get_mut(parent.get()) {
+            let speed = rate * orbit.angular_speed.signum();
+            if orbit.angular_speed != speed {
+                orbit.angular_speed = speed;
+            }
+        }
+        let (mean_motion, angular_speed) = (rate * ellipse.mean_motion.signum(), rate * ellipse.angular_speed.signum());
+        if (ellipse.mean_motion, ellipse.angular_speed) != (mean_motion, angular_speed) {
+            (ellipse.mean_motion, ellipse.angular_speed) = (mean_motion, angular_speed);
+        }
+        if ellipse.a == radius {
+            continue;
+        }
+        ellipse.a = radius;
+        ellipse.b = radius * (1.0 - ellipse.eccentricity * ellipse.eccentricity).sqrt();
+        if ellipse.active && !params.physics_mode {
+            transform.translation = ellipse.position(params.use_kepler_motion);
+        }
+    }
+}
+
+/// System: advances `SimClock` by the scaled frame time unless paused.
+/// Earth's orbital angle accumulates at its pivot's rate, so one revolution = one year.
+pub(crate) fn tick_clock(
+    mut clock: ResMut<SimClock>,
+    earth_pivot_q: Query<&Orbit, With<EarthPivot>>,
+    time: Res<Time>,
+    params: Res<SimulationParams>,
+) {
+    if params.paused {
+        return;
+    }
+    let scaled_dt = params.sim_dt(&time, &clock) * f64::from(params.orbit_speed_scale);
+    clock.sim_seconds += scaled_dt;
+    if let Ok(orbit) = earth_pivot_q.get_single() {
+        clock.earth_orbit_angle += f64::from(orbit.angular_speed.abs() * params.planet_orbit_scale) * scaled_dt;
+    }
+}
+
+/// System: auto slow-mo. Near a Sun–Earth–Moon alignment (the eclipse geometry, both in
+/// elongation and ecliptic latitude) eases `SimClock::time_dilation` down toward
+/// `slow_mo_floor`, then back up to 1 once the bodies separate. The factor multiplies the
+/// speed sliders rather than rewriting them, so dragging a slider mid-event just works.
+/// Eased on wall-clock time so the slow-down doesn't slow its own recovery.
+pub(crate) fn auto_slow_mo(
+    mut clock: ResMut<SimClock>,
+    sun_q: Query<&GlobalTransform, With<Sun>>,
+    earth_q: Query<&GlobalTransform, With<Earth>>,
+    moon_q: Query<&GlobalTransform, With<Moon>>,
+    params: Res<SimulationParams>,
+    real_time: Res<Time<Real>>,
+) {
+    const WINDOW_DEG: f32 = 25.0; // misalignment where the slow-down starts; the Moon covers ~9° per frame at 60 FPS
+    const EASE_IN: f32 = 0.05; // s: brake quickly so fast alignments aren't missed
+    const EASE_OUT: f32 = 0.5; // s: recover gently
+    let target = match (sun_q.get_single(), earth_q.get_single(), moon_q.get_single()) {
+        (Ok(sun), Ok(earth), Ok(moon)) if params.auto_slow_mo => {
+            let geometry = Syzygy::new(sun.translation(), earth.translation(), moon.translation());
+            let elongation = geometry.elongation.abs();
+            let miss = elongation.min(PI - elongation).max(geometry.latitude.abs());
+            let window = WINDOW_DEG.max(params.eclipse_threshold_deg).to_radians();
+            let t = (miss / window).min(1.0);
+            let floor = params.slow_mo_floor;
+            floor + (1.0 - floor) * t * t * (3.0 - 2.0 * t) // smoothstep from the floor at alignment
+        }
+        _ => 1.0,
+    };
+    let ease = if target < clock.time_dilation { EASE_IN } else { EASE_OUT };
+    let blend = 1.0 - (-real_time.delta_seconds() / ease).exp();
+    let dilation = clock.time_dilation + (target - clock.time_dilation) * blend;
+    if (dilation - target).abs() < 1e-4 {
+        clock.time_dilation = target;
+    } else {
+        clock.time_dilation = dilation;
+    }
+}
+
+/// Center of mass of two bodies.
+pub(crate) fn barycenter(a: Vec3, mass_a: f32, b: Vec3, mass_b: f32) -> Vec3 {
+    (a * mass_a + b * mass_b) / (mass_a + mass_b)
+}
+
+/// Resource: Earth's own local translation while `apply_barycentric_shift` has it moved
+/// for this frame's transform propagation; put back by `restore_barycentric_shift`.
+#[derive(Resource, Default)]
+pub(crate) struct BarycentricShift(Option<Vec3>);
+
+/// System: with `barycentric_motion`, moves Earth off its orbit point by the Moon's share
+/// of their separation, so the orbit point becomes the Earth–Moon barycenter and Earth
+/// circles it once a month. The Moon rides along (it is Earth's child), which puts it at
+/// its own share on the far side. Uses last frame's separation: the shift moves both
+/// bodies, so it doesn't feed back. Physics mode integrates the wobble itself.
+pub(crate) fn apply_barycentric_shift(
+    mut shift: ResMut<BarycentricShift>,
+    mut earth_q: Query<(&mut Transform, &GlobalTransform, &Mass, &Parent), With<Earth>>,
+    moon_q: Query<(&GlobalTransform, &Mass), With<Moon>>,
+    frames: Query<&GlobalTransform, Without<Earth>>,
+    params: Res<SimulationParams>,
+) {
+    if !params.barycentric_motion || params.physics_mode {
+        return;
+    }
+    let (Ok((mut transform, earth, earth_mass, parent)), Ok((moon, moon_mass))) =
+        (earth_q.get_single_mut(), moon_q.get_single())
+    else {
+        return;
+    };
+    let Ok(frame) = frames.get(parent.get()) else { return };
+    let (earth, moon) = (earth.translation(), moon.translation());
+    let offset = barycenter(earth, earth_mass.0, moon, moon_mass.0) - earth;
+    shift.0 = Some(transform.translation);
+    transform.translation -= frame.affine().inverse().transform_vector3(offset);
+}
+
+/// System: undoes `apply_barycentric_shift` once transforms have propagated, so the orbit
+/// systems never see (or accumulate) the offset.
+pub(crate) fn restore_barycentric_shift(mut shift: ResMut<BarycentricShift>, mut earth_q: Query<&mut Transform, With<Earth>>) {
+    let Some(base) = shift.0.take() else { return };
+    if let Ok(mut transform) = earth_q.get_single_mut() {
+        transform.translation = base;
+    }
+}
+
+/// System: estimates each body's world velocity from its position change since last frame.
+pub(crate) fn track_motion(mut q: Query<(&GlobalTransform, &mut TrackedMotion)>, time: Res<Time>) {
+    let dt = time.delta_seconds();
+    for (global, mut motion) in &mut q {
+        let position = global.translation();
+        if let (Some(last), true) = (motion.last_position, dt > 0.0) {
+            motion.velocity = (position - last) / dt;
+        }
+        motion.last_position = Some(position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::time::TimeUpdateStrategy;
+    use std::f64::consts::FRAC_PI_2;
+    use std::time::Duration;
+    use crate::headless_app;
+
+    #[test]
+    fn angles_stay_exact_over_millions_of_steps() {
+        const STEPS: u32 = 10_000_000;
+        let params = SimulationParams::default();
+        let dt = f64::from(params.max_frame_dt) * params.time_scale; // the largest step a frame takes
+        let radius = params.earth_orbit_radius;
+        for rate in [rate_for_period(EARTH_YEAR_DAYS), rate_for_period(EARTH_DAY_DAYS)].map(f64::from) {
+            let mut angle = 0.0;
+            for _ in 0..STEPS {
+                angle = advance_angle(angle, rate, dt);
+            }
+            let t = f64::from(STEPS) * dt;
+            let exact = (rate * t).rem_euclid(std::f64::consts::TAU);
+            // Nearly equal angles either side of the wrap are nearly a turn apart
+            let error = (angle - exact).abs().min(std::f64::consts::TAU - (angle - exact).abs());
+            assert!(error < 1e-6, "rate {rate}: {angle} vs closed form {exact} ({error} rad off)");
+            let position = Quat::from_rotation_y(angle as f32) * Vec3::X * radius;
+            assert!((position.length() - radius).abs() < 1e-5, "left the circle: |{position}| ≠ {radius}");
+            assert!(position.distance(Quat::from_rotation_y(exact as f32) * Vec3::X * radius) < 1e-4);
+        }
+    }
+
+    /// Earth's world up vector (its spin axis) with its pivot yawed by `phase` along the
+    /// orbit and the body spun by `spin`, as `apply_rotations` poses them.
+    fn earth_axis(phase: f64, spin: f64) -> Vec3 {
+        let mut world = World::new();
+        let tilt = Quat::from_axis_angle(Vec3::Z, EARTH_AXIAL_TILT_DEG.to_radians());
+        let pivot = world.spawn((Transform::default(), InitialPose(Quat::IDENTITY), Orbit { yaw: phase, ..Orbit::new(1.0) })).id();
+        let earth = world.spawn((Transform::default(), InitialPose(tilt), Spin { angle: spin, ..Spin::new(1.0) }, InertialAxis)).id();
+        world.entity_mut(pivot).add_child(earth);
+        world.insert_resource(SimulationParams::default());
+        world.run_system_once(apply_rotations);
+        let rotation = world.get::<Transform>(pivot).unwrap().rotation * world.get::<Transform>(earth).unwrap().rotation;
+        rotation * Vec3::Y
+    }
+
+    /// Earth's pivot yaw after `frames` frames of `frame` each in a headless app, with fixed
+    /// stepping on or off. The startup update comes first and adds no time.
+    fn earth_yaw_after(frame: Duration, frames: u32, fixed: bool) -> f64 {
+        let mut app = headless_app();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(frame));
+        app.world_mut().resource_mut::<SimulationParams>().fixed_timestep = fixed;
+        for _ in 0..=frames {
+            app.update();
+        }
+        let world = app.world_mut();
+        world.query_filtered::<&Orbit, With<EarthPivot>>().single(world).yaw
+    }
+
+    #[test]
+    fn fixed_timestep_ignores_the_frame_rate() {
+        // 10.13 s either way: clear of a 64 Hz fixed-step boundary, so rounding in the frame
+        // durations can't change how many fixed steps fit
+        const FRAMES: u32 = 608;
+        let (fps_30, fps_60) = (Duration::from_secs_f64(1.0 / 30.0), Duration::from_secs_f64(1.0 / 60.0));
+        let fixed = earth_yaw_after(fps_30, FRAMES / 2, true);
+        assert!(fixed > 0.0, "Earth didn't move");
+        assert_eq!(fixed.to_bits(), earth_yaw_after(fps_60, FRAMES, true).to_bits(), "fixed steps depend on the frame rate");
+        // Per-frame stepping sums different steps, so it doesn't land on the same bits
+        assert_ne!(earth_yaw_after(fps_30, FRAMES / 2, false), earth_yaw_after(fps_60, FRAMES, false));
+    }
+
+    #[test]
+    fn earth_axis_keeps_its_tilt_around_the_orbit() {
+        let expected = Quat::from_axis_angle(Vec3::Z, EARTH_AXIAL_TILT_DEG.to_radians()) * Vec3::Y;
+        for phase in [0.0, FRAC_PI_2, 2.0 * FRAC_PI_2, 3.0 * FRAC_PI_2] {
+            for spin in [0.0, 1.0, 4.0] {
+                let axis = earth_axis(phase, spin);
+                assert!(axis.abs_diff_eq(expected, 1e-5), "phase {phase}, spin {spin}: axis {axis}, expected {expected}");
+                let tilt = axis.angle_between(Vec3::Y).to_degrees();
+                assert!((tilt - EARTH_AXIAL_TILT_DEG).abs() < 1e-3, "phase {phase}: tilt {tilt}°");
+            }
+        }
+    }
+
+    #[test]
+    fn sanitized_ellipses_have_finite_positions() {
+        let broken = [
+            EllipticalOrbit { eccentricity: 1.0, ..EllipticalOrbit::new(5.0, 0.5, 1.0) },
+            EllipticalOrbit { eccentricity: 3.0, ..EllipticalOrbit::new(5.0, 0.5, 1.0) },
+            EllipticalOrbit { eccentricity: -0.5, ..EllipticalOrbit::new(5.0, 0.5, 1.0) },
+            EllipticalOrbit { eccentricity: f32::NAN, ..EllipticalOrbit::new(5.0, 0.5, 1.0) },
+            EllipticalOrbit { a: -4.0, b: -2.0, ..EllipticalOrbit::new(5.0, 0.5, 1.0) },
+            EllipticalOrbit { a: 0.0, b: 0.0, ..EllipticalOrbit::new(5.0, 0.5, 1.0) },
+            EllipticalOrbit { a: f32::NAN, b: f32::NAN, ..EllipticalOrbit::new(5.0, 0.5, 1.0) },
+            EllipticalOrbit { a: 5.0, b: 9.0, theta: f32::NAN, mean_anomaly: f32::INFINITY, ..EllipticalOrbit::new(5.0, 0.5, 1.0) },
+        ];
+        for mut orbit in broken {
+            let shape = (orbit.a, orbit.b, orbit.eccentricity);
+            assert!(orbit.sanitize(), "{shape:?} should have needed fixing");
+            assert!((0.0..1.0).contains(&orbit.eccentricity), "{shape:?}: e = {}", orbit.eccentricity);
+            assert!(orbit.a > 0.0 && orbit.b > 0.0 && orbit.b <= orbit.a, "{shape:?}: a = {}, b = {}", orbit.a, orbit.b);
+            for angle in [0.0, 1.0, PI, 5.0] {
+                orbit.theta = angle;
+                orbit.mean_anomaly = angle;
+                for kepler in [true, false] {
+                    let position = orbit.position(kepler);
+                    assert!(position.is_finite(), "{shape:?}, kepler {kepler}, angle {angle}: {position}");
+                }
+            }
+            assert!(!orbit.sanitize(), "{shape:?}: a sanitized orbit should stay put");
+        }
+    }
+
+    /// `(r × v).y` of Earth about the Sun over the next update of `app`: positive when it
+    /// goes round counter-clockwise seen from above, as the pivots turn.
+    fn earth_turning(app: &mut App) -> f32 {
+        let earth_sun = |app: &mut App| {
+            let world = app.world_mut();
+            let earth = world.query_filtered::<&GlobalTransform, With<Earth>>().single(world).translation();
+            let sun = world.query_filtered::<&GlobalTransform, With<Sun>>().single(world).translation();
+            (earth, sun)
+        };
+        let (before, _) = earth_sun(app);
+        app.update();
+        let (after, sun) = earth_sun(app);
+        (after - sun).cross(after - before).y
+    }
+
+    #[test]
+    fn switching_orbit_modes_keeps_the_direction_of_travel() {
+        for kepler in [true, false] {
+            let mut app = headless_app();
+            app.world_mut().resource_mut::<SimulationParams>().use_kepler_motion = kepler;
+            for _ in 0..3 {
+                app.update();
+            }
+            let circular = earth_turning(&mut app);
+            assert!(circular > 0.0, "kepler {kepler}: circular orbit turning {circular}");
+            for elliptical in [true, false, true] {
+                app.world_mut().resource_mut::<SimulationParams>().use_elliptical_orbit = elliptical;
+                app.update(); // the switch itself
+                let turning = earth_turning(&mut app);
+                assert!(turning * circular > 0.0, "kepler {kepler}, elliptical {elliptical}: turning {turning}, was {circular}");
+            }
+        }
+    }
+}
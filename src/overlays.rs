@@ -0,0 +1,549 @@
+//! Gizmo overlays: guides, trails, markers, and debug views.
+
+use bevy::color::{palettes::css, LinearRgba};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use std::f32::consts::{PI, TAU};
+use crate::{orbits::*, params::*, physics::*, scene::*};
+
+/// Component: ring buffer of recent positions, drawn as a polyline.
+/// Recorded from `GlobalTransform`, so a Moon trail already combines both pivots.
+/// Positions are relative to the trail frame body (see `SimulationParams::trail_frame`),
+/// which is the world origin unless a reference frame is chosen.
+/// Each point carries the real time it was sampled at, so fading is frame-rate independent.
+#[derive(Component)]
+pub(crate) struct OrbitTrail {
+    pub(crate) points: VecDeque<(Vec3, f32)>, // (position, real elapsed seconds); oldest at the front
+    color: Color,
+}
+
+impl OrbitTrail {
+    pub(crate) const MAX_LEN: usize = 2048; // upper bound for the trail-length slider
+
+    pub(crate) fn new(color: Color) -> Self {
+        Self {
+            points: VecDeque::with_capacity(Self::MAX_LEN),
+            color,
+        }
+    }
+}
+
+/// Query data for an ellipse `draw_ecliptic_guides` may draw.
+type GuidedEllipse = (&'static EllipticalOrbit, &'static Parent, Option<&'static Planet>, Has<Earth>, Has<Moon>);
+
+/// System: depth references. The ecliptic grid lies on world XZ through the origin. The
+/// orbit paths show where each body will go (trails only show where it has been): bodies
+/// riding an ellipse get it sampled from the ellipse equation in their parent's frame (the
+/// Moon's pivot, for the Moon's); the rest get circles at the live radius params (Earth,
+/// Moon) or the `PLANETS` radii, with the Moon's drawn around Earth in its inclined plane.
+pub(crate) fn draw_ecliptic_guides(
+    mut gizmos: Gizmos,
+    earth_q: Query<&GlobalTransform, With<Earth>>,
+    moon_plane_q: Query<&GlobalTransform, With<MoonOrbitPlane>>,
+    ellipses: Query<GuidedEllipse>,
+    frames: Query<&GlobalTransform>,
+    params: Res<SimulationParams>,
+) {
+    if params.show_ecliptic_grid && params.grid_spacing > 0.0 {
+        let cells = (2.0 * params.grid_extent / params.grid_spacing).round().max(1.0) as u32;
+        gizmos.grid(
+            Vec3::ZERO,
+            Quat::from_rotation_x(PI / 2.0), // grids are drawn in XY; lay it flat on XZ
+            UVec2::splat(cells),
+            Vec2::splat(params.grid_spacing),
+            LinearRgba::gray(0.25),
+        );
+    }
+    if !params.show_orbit_circles {
+        return;
+    }
+    let [r, g, b, a] = params.orbit_path_color;
+    let color = Color::srgba(r, g, b, a);
+    let samples = params.orbit_path_samples.max(3);
+    let (mut earth_on_ellipse, mut moon_on_ellipse, mut planets_on_ellipse) = (false, false, Vec::new());
+    for (ellipse, parent, planet, is_earth, is_moon) in ellipses.iter().filter(|(e, ..)| e.active) {
+        let Ok(frame) = frames.get(parent.get()) else { continue };
+        gizmos.linestrip(ellipse.path(samples).map(|p| frame.transform_point(p)), color);
+        earth_on_ellipse |= is_earth;
+        moon_on_ellipse |= is_moon;
+        planets_on_ellipse.extend(planet.map(|p| p.name));
+    }
+    if !earth_on_ellipse {
+        gizmos.circle(Vec3::ZERO, Dir3::Y, params.earth_orbit_radius, color).resolution(samples as usize);
+    }
+    for (_, &radius) in PLANETS.iter().zip(&params.planet_orbit_radii).filter(|(p, _)| !planets_on_ellipse.contains(&p.name)) {
+        gizmos.circle(Vec3::ZERO, Dir3::Y, radius, color).resolution(samples as usize);
+    }
+    if let (false, Ok(earth), Ok(plane)) = (moon_on_ellipse, earth_q.get_single(), moon_plane_q.get_single()) {
+        gizmos.circle(earth.translation(), plane.up(), params.moon_orbit_radius, color).resolution(samples as usize);
+    }
+}
+
+/// Where a closed path, in order of motion, crosses the horizontal plane at height `y`:
+/// its upward crossing (the ascending node) and downward crossing (the descending node),
+/// interpolated along the crossing segment. Points within a hair of the plane are skipped,
+/// so a flat orbit has no nodes rather than ones scattered by rounding; the path is walked
+/// twice, so a node on its first point (skipped too) is still found.
+fn node_crossings(path: &[Vec3], y: f32) -> (Option<Vec3>, Option<Vec3>) {
+    const ON_PLANE: f32 = 1e-4;
+    let (mut ascending, mut descending) = (None, None);
+    let mut last: Option<(Vec3, f32)> = None; // last point clearly off the plane, and its height
+    for &point in path.iter().chain(path) {
+        let height = point.y - y;
+        if height.abs() < ON_PLANE {
+            continue;
+        }
+        if let Some((prev, prev_height)) = last.filter(|(_, h)| h.signum() != height.signum()) {
+            let node = prev.lerp(point, prev_height / (prev_height - height));
+            *if height > 0.0 { &mut ascending } else { &mut descending } = Some(node);
+        }
+        last = Some((point, height));
+    }
+    (ascending, descending)
+}
+
+/// System: for every inclined orbit (the Moon's, and any tilted ellipse), draws the line of
+/// nodes, where the orbit plane cuts the ecliptic plane through its focus, with a cyan
+/// marker at the ascending node (heading north) and a magenta one at the descending node.
+/// The nodes are found on the sampled path itself, so they follow the inclination and
+/// orientation sliders live. Eclipses can only happen when the Moon is near one of its
+/// nodes at new or full moon.
+pub(crate) fn draw_nodes(
+    mut gizmos: Gizmos,
+    earth_q: Query<&GlobalTransform, With<Earth>>,
+    moon_plane_q: Query<&GlobalTransform, With<MoonOrbitPlane>>,
+    ellipses: Query<(&EllipticalOrbit, &Parent, Has<Moon>)>,
+    frames: Query<&GlobalTransform>,
+    params: Res<SimulationParams>,
+) {
+    if !params.show_nodes {
+        return;
+    }
+    let samples = params.orbit_path_samples.max(3);
+    let mut orbits = Vec::new(); // (focus, path in world space)
+    let mut moon_on_ellipse = false;
+    for (ellipse, parent, is_moon) in ellipses.iter().filter(|(e, ..)| e.active) {
+        let Ok(frame) = frames.get(parent.get()) else { continue };
+        orbits.push((frame.translation(), ellipse.path(samples).map(|p| frame.transform_point(p)).collect::<Vec<_>>()));
+        moon_on_ellipse |= is_moon;
+    }
+    if let (false, Ok(earth), Ok(plane)) = (moon_on_ellipse, earth_q.get_single(), moon_plane_q.get_single()) {
+        // The way the Moon's pivot carries it: +X turned about the plane's Y
+        let path = (0..=samples).map(|i| {
+            let angle = i as f32 / samples as f32 * TAU;
+            plane.transform_point(Quat::from_rotation_y(angle) * Vec3::X * params.moon_orbit_radius)
+        });
+        orbits.push((earth.translation(), path.collect()));
+    }
+    for (focus, path) in orbits {
+        let (Some(ascending), Some(descending)) = node_crossings(&path, focus.y) else { continue };
+        gizmos.line(ascending, descending, css::GOLD.with_alpha(0.6));
+        gizmos.sphere(ascending, Quat::IDENTITY, 0.06, css::AQUA);
+        gizmos.sphere(descending, Quat::IDENTITY, 0.06, css::FUCHSIA);
+    }
+}
+
+/// Simulated years of Mars's apparent path kept by `draw_retrograde_path`: a little more
+/// than one synodic period (≈ 2.1 years), so every window contains an opposition.
+const RETROGRADE_SPAN_YEARS: f64 = 2.5;
+
+/// State for `draw_retrograde_path`.
+#[derive(Default)]
+pub(crate) struct RetrogradeTrack {
+    samples: VecDeque<(Vec3, f64, bool)>, // (Earth→Mars unit direction, sim years, moving retrograde)
+    last_longitude: Option<f32>,
+}
+
+/// The starfield sphere `draw_retrograde_path` draws on: where it's centered and its size.
+#[derive(SystemParam)]
+pub(crate) struct SkySphere<'w, 's> {
+    sky_q: Query<'w, 's, &'static GlobalTransform, With<Skybox>>,
+    starfield: Res<'w, StarfieldConfig>,
+}
+
+/// System: Mars's apparent motion. Samples the Earth→Mars direction once per simulated day,
+/// and draws the recent ones on the starfield sphere (centered on the camera, like the
+/// stars, so the path sits among them). Ecliptic longitude normally increases; while Earth
+/// overtakes Mars near opposition it runs backwards, and those samples are drawn red. Our
+/// orbits are coplanar, so the backtrack lies on the ecliptic itself; `retrograde_spread`
+/// lifts older samples a little, turning it into a visible loop. Clears on rewinds and warps.
+pub(crate) fn draw_retrograde_path(
+    mut gizmos: Gizmos,
+    mut track: Local<RetrogradeTrack>,
+    earth_q: Query<&GlobalTransform, With<Earth>>,
+    planets: Query<(&Planet, &GlobalTransform)>,
+    sky: SkySphere,
+    clock: Res<SimClock>,
+    params: Res<SimulationParams>,
+) {
+    let SkySphere { sky_q, starfield } = sky;
+    let mars = planets.iter().find(|(planet, _)| planet.name == "Mars");
+    let (Some((_, mars)), Ok(earth), true) = (mars, earth_q.get_single(), params.show_retrograde) else {
+        *track = RetrogradeTrack::default();
+        return;
+    };
+    let years = clock.years();
+    if track.samples.back().is_some_and(|&(_, last, _)| years < last) {
+        *track = RetrogradeTrack::default(); // rewound, warped back, or reset
+    }
+    if !track.samples.back().is_some_and(|&(_, last, _)| years - last < 1.0 / 365.25) {
+        let dir = (mars.translation() - earth.translation()).normalize_or_zero();
+        // Orbits turn +Y, which carries +X toward -Z: longitude = atan2(-z, x)
+        let longitude = (-dir.z).atan2(dir.x);
+        let retrograde = track.last_longitude.is_some_and(|last| {
+            let step = (longitude - last + PI).rem_euclid(TAU) - PI;
+            step < 0.0
+        });
+        track.last_longitude = Some(longitude);
+        track.samples.push_back((dir, years, retrograde));
+        while track.samples.front().is_some_and(|&(_, first, _)| years - first > RETROGRADE_SPAN_YEARS) {
+            track.samples.pop_front();
+        }
+    }
+
+    let center = sky_q.get_single().map_or(Vec3::ZERO, |sky| sky.translation());
+    let radius = starfield.radius * 0.98; // just inside the stars
+    let on_sky = |dir: Vec3, age: f64| {
+        if !params.retrograde_spread {
+            return center + dir * radius;
+        }
+        // Tip toward +Y by up to ~12° at the oldest sample, about the horizontal perpendicular
+        let axis = dir.cross(Vec3::Y).normalize_or_zero();
+        let lift = (age / RETROGRADE_SPAN_YEARS) as f32 * 12f32.to_radians();
+        center + Quat::from_axis_angle(axis, -lift) * dir * radius
+    };
+    let prograde = Color::srgb(0.8, 0.35, 0.2).with_alpha(0.9);
+    let backwards = Color::srgb(1.0, 0.1, 0.1);
+    gizmos.linestrip_gradient(
+        track.samples.iter().map(|&(dir, t, retro)| (on_sky(dir, years - t), if retro { backwards } else { prograde })),
+    );
+    if let Some(&(dir, _, _)) = track.samples.back() {
+        let now = on_sky(dir, 0.0);
+        gizmos.sphere(now, Quat::IDENTITY, radius * 0.006, Color::WHITE);
+    }
+}
+
+/// System: day markers on Earth. An orange meridian line from Earth's center through a
+/// fixed point on its equator (local +X, turning with the spin), a yellow line toward the
+/// Sun, and a white one toward a fixed star (world +X). The meridian passes the star line
+/// once per sidereal day but only catches up with the Sun once per (longer) solar day,
+/// since the Sun line drifts round as Earth orbits.
+pub(crate) fn draw_day_markers(
+    mut gizmos: Gizmos,
+    earth_q: Query<(&GlobalTransform, &BodyRadius), With<Earth>>,
+    sun_q: Query<&GlobalTransform, With<Sun>>,
+    params: Res<SimulationParams>,
+) {
+    if !params.show_day_markers {
+        return;
+    }
+    let (Ok((earth, radius)), Ok(sun)) = (earth_q.get_single(), sun_q.get_single()) else { return };
+    let (scale, _, center) = earth.to_scale_rotation_translation();
+    let length = 1.8 * radius.0 * scale.max_element(); // poke out past the surface
+    let meridian = earth.affine().transform_vector3(Vec3::X).normalize_or_zero();
+    let to_sun = (sun.translation() - center).normalize_or_zero();
+    gizmos.line(center, center + meridian * length, css::ORANGE);
+    gizmos.line(center, center + to_sun * length, css::YELLOW);
+    gizmos.line(center, center + Vec3::X * length, css::WHITE);
+}
+
+/// Query data for a body `debug_day_night` shades: everything pickable but the Sun.
+type ShadedBody = (&'static GlobalTransform, &'static BodyRadius, &'static Handle<Mesh>, &'static Handle<StandardMaterial>);
+
+/// A mesh `debug_day_night` has colored, and the Sun's direction in its frame at the time.
+type ShadedMesh = (AssetId<Mesh>, Vec3);
+
+/// System: lighting debug view. Independently of the renderer, colors every vertex of each
+/// body by the dot product of its world normal with the direction to the Sun, gold by day
+/// and blue by night with a red band along the terminator, and switches the materials to
+/// unlit so the tint shows as computed. A gizmo great circle perpendicular to the Sun
+/// direction marks where the real shading's terminator should fall. The colors only depend
+/// on the Sun's direction in the mesh's own frame, so a mesh is recolored (and re-uploaded)
+/// only once that has turned by `RECOLOR_ANGLE` since its last coloring, as the body spins
+/// or orbits. Turning it off strips the vertex colors and relights the materials.
+pub(crate) fn debug_day_night(
+    mut gizmos: Gizmos,
+    bodies: Query<ShadedBody, Without<Sun>>,
+    sun_q: Query<&GlobalTransform, With<Sun>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    params: Res<SimulationParams>,
+    mut shaded: Local<Option<Vec<ShadedMesh>>>, // `Some` while on
+) {
+    const TERMINATOR_BAND: f32 = 0.04; // |cos| below this counts as on the line
+    const RECOLOR_ANGLE: f32 = 0.5 * PI / 180.0; // half a degree: finer than the mesh's facets
+    let on = params.debug_day_night;
+    if !on && shaded.is_none() {
+        return;
+    }
+    let colored = shaded.get_or_insert_with(Vec::new);
+    let sun = sun_q.get_single().map_or(Vec3::ZERO, GlobalTransform::translation);
+    for (global, radius, mesh, material) in &bodies {
+        // Only touch the material when the flag flips, so it isn't re-uploaded every frame
+        if materials.get(material).is_some_and(|m| m.unlit != on) {
+            if let Some(material) = materials.get_mut(material) {
+                material.unlit = on;
+            }
+        }
+        let id = mesh.id();
+        if !on {
+            if let Some(mesh) = meshes.get_mut(mesh) {
+                mesh.remove_attribute(Mesh::ATTRIBUTE_COLOR);
+            }
+            continue;
+        }
+        let (scale, rotation, center) = global.to_scale_rotation_translation();
+        let Ok(to_sun) = Dir3::new(sun - center) else { continue };
+        let r = radius.0 * scale.max_element() * 1.02; // just above the surface
+        gizmos.circle(center, to_sun, r, css::RED).resolution(48);
+        let local_sun = rotation.inverse() * *to_sun;
+        let last = colored.iter().position(|&(colored_id, _)| colored_id == id);
+        // A rebuilt mesh (new detail level) comes back without colors
+        let has_colors = meshes.get(mesh).is_some_and(|m| m.contains_attribute(Mesh::ATTRIBUTE_COLOR));
+        if has_colors && last.is_some_and(|i| colored[i].1.angle_between(local_sun) < RECOLOR_ANGLE) {
+            continue;
+        }
+        let Some(mesh) = meshes.get_mut(mesh) else { continue };
+        let Some(normals) = mesh.attribute(Mesh::ATTRIBUTE_NORMAL).and_then(|n| n.as_float3()) else { continue };
+        let colors: Vec<[f32; 4]> = normals
+            .iter()
+            .map(|&n| {
+                let cos = Vec3::from_array(n).dot(local_sun);
+                let ([r, g, b], brightness) = if cos.abs() < TERMINATOR_BAND {
+                    ([1.0, 0.0, 0.0], 1.0)
+                } else if cos > 0.0 {
+                    ([1.0, 0.8, 0.3], 0.4 + 0.6 * cos)
+                } else {
+                    ([0.15, 0.25, 1.0], 0.4 - 0.6 * cos)
+                };
+                [r * brightness, g * brightness, b * brightness, 1.0]
+            })
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        match last {
+            Some(i) => colored[i].1 = local_sun,
+            None => colored.push((id, local_sun)),
+        }
+    }
+    if !on {
+        *shaded = None;
+    }
+}
+
+/// System: marks the perihelion (yellow) and aphelion (red) on the major axis of each
+/// active Earth-style ellipse, a − c and a + c from the focus (the Sun, or Earth for the
+/// Moon's perigee and apogee), placed through the parent pivot's frame (the ellipse is
+/// drawn in that frame's XZ plane).
+pub(crate) fn draw_apsides(
+    mut gizmos: Gizmos,
+    q: Query<(&EllipticalOrbit, &Parent), Without<Comet>>,
+    frames: Query<&GlobalTransform>,
+) {
+    for (ellipse, parent) in q.iter().filter(|(e, _)| e.active) {
+        let Ok(frame) = frames.get(parent.get()) else { continue };
+        let (perihelion, aphelion) = ellipse.apsides();
+        gizmos.sphere(frame.transform_point(Vec3::X * perihelion), Quat::IDENTITY, 0.1, css::YELLOW);
+        gizmos.sphere(frame.transform_point(Vec3::NEG_X * aphelion), Quat::IDENTITY, 0.1, css::RED);
+    }
+}
+
+/// System: draws the comet's tail as a fan of gizmo lines pointing away from the Sun
+/// (solar wind blows it anti-sunward whichever way the comet moves). It grows as 1/r
+/// toward perihelion, as the ices heat up.
+pub(crate) fn draw_comet_tail(
+    mut gizmos: Gizmos,
+    sun_q: Query<&GlobalTransform, With<Sun>>,
+    comet_q: Query<&GlobalTransform, With<Comet>>,
+) {
+    const STRANDS: usize = 9;
+    let Ok(sun) = sun_q.get_single() else { return };
+    for comet in &comet_q {
+        let offset = comet.translation() - sun.translation();
+        let distance = offset.length();
+        if distance < f32::EPSILON {
+            continue;
+        }
+        let away = offset / distance;
+        let length = (12.0 / distance).min(6.0);
+        let side = away.any_orthonormal_vector();
+        for i in 0..STRANDS {
+            // Spread the strands over a narrow fan, brightest along the axis
+            let t = i as f32 / (STRANDS - 1) as f32 * 2.0 - 1.0;
+            let dir = Quat::from_axis_angle(side.cross(away), t * 0.12) * away;
+            let alpha = 0.7 * (1.0 - t.abs() * 0.7);
+            gizmos.line_gradient(
+                comet.translation(),
+                comet.translation() + dir * length * (1.0 - 0.3 * t.abs()),
+                Color::srgba(0.7, 0.85, 1.0, alpha),
+                Color::srgba(0.7, 0.85, 1.0, 0.0),
+            );
+        }
+    }
+}
+
+/// World position of the body named `frame`, or the origin for the Sun-centered world
+/// frame (None) and for a name that no longer matches any body.
+fn frame_origin<'a>(frame: Option<&str>, bodies: impl IntoIterator<Item = (&'a Name, &'a GlobalTransform)>) -> Vec3 {
+    let Some(frame) = frame else { return Vec3::ZERO };
+    bodies.into_iter().find(|(name, _)| name.as_str() == frame).map_or(Vec3::ZERO, |(_, global)| global.translation())
+}
+
+/// System: appends each trailed body's current position, relative to the trail frame
+/// body, to its ring buffer, dropping the oldest samples beyond `trail_length` (or, with
+/// `trail_fade`, once fully faded). Switching frames clears every trail, since the old
+/// samples are relative to a different body. Runs after transform propagation
+/// so the sample matches what was rendered this frame.
+pub(crate) fn record_trails(
+    mut q: Query<(&GlobalTransform, &mut OrbitTrail)>,
+    bodies: Query<(&Name, &GlobalTransform), With<BodyRadius>>,
+    params: Res<SimulationParams>,
+    real_time: Res<Time<Real>>,
+    mut last_frame: Local<Option<String>>,
+) {
+    if last_frame.as_deref() != params.trail_frame() {
+        *last_frame = params.trail_frame().map(str::to_string);
+        for (_, mut trail) in &mut q {
+            trail.points.clear();
+        }
+    }
+    let origin = frame_origin(params.trail_frame(), &bodies);
+    let now = real_time.elapsed_seconds();
+    for (global, mut trail) in &mut q {
+        trail.points.push_back((global.translation() - origin, now));
+        while trail.points.len() > params.trail_length {
+            trail.points.pop_front();
+        }
+        if params.trail_fade {
+            while trail.points.front().is_some_and(|&(_, stamp)| now - stamp > params.trail_fade_secs) {
+                trail.points.pop_front();
+            }
+        }
+    }
+}
+
+/// System: draws each trail as a gizmo polyline through its buffered points. Colors run
+/// from the newest point to the oldest along the gradient (or stay the body's own color),
+/// and with `trail_fade` the alpha drops linearly with each point's real-time age.
+/// Points are placed around the trail frame body's current position, so in Earth's frame
+/// the Moon's trail is a closed loop that travels along with Earth.
+pub(crate) fn draw_trails(
+    mut gizmos: Gizmos,
+    q: Query<&OrbitTrail>,
+    bodies: Query<(&Name, &GlobalTransform), With<BodyRadius>>,
+    params: Res<SimulationParams>,
+    real_time: Res<Time<Real>>,
+) {
+    if !params.show_trails {
+        return;
+    }
+    let origin = frame_origin(params.trail_frame(), &bodies);
+    let now = real_time.elapsed_seconds();
+    let (start, end) = (Vec3::from(params.trail_gradient_start), Vec3::from(params.trail_gradient_end));
+    for trail in &q {
+        let count = trail.points.len();
+        let colored = trail.points.iter().enumerate().map(|(i, &(point, stamp))| {
+            let alpha = if params.trail_fade {
+                (1.0 - (now - stamp) / params.trail_fade_secs).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+            let color = if params.trail_gradient {
+                // Oldest point (index 0) gets the end color, newest the start color
+                let rgb = end.lerp(start, i as f32 / (count - 1).max(1) as f32);
+                Color::srgb(rgb.x, rgb.y, rgb.z)
+            } else {
+                trail.color
+            };
+            (origin + point, color.with_alpha(alpha))
+        });
+        gizmos.linestrip_gradient(colored);
+    }
+}
+
+/// Sun–Earth Lagrange points L1..L5 for mass ratio `mu = m_earth / (m_sun + m_earth)`,
+/// in the orbital plane (normal = world Y). L1/L2 use the Hill-radius approximation
+/// r·∛(μ/3) either side of Earth, L3 sits opposite at r·(1 + 5μ/12) from the Sun, and
+/// L4/L5 lead/trail Earth by 60° (equilateral triangles with the Sun).
+fn lagrange_points(sun: Vec3, earth: Vec3, mu: f32) -> [Vec3; 5] {
+    let offset = earth - sun;
+    let r = offset.length();
+    let dir = offset / r;
+    let hill = r * (mu / 3.0).cbrt();
+    let around = |angle: f32| sun + Quat::from_rotation_y(angle) * offset;
+    [
+        earth - dir * hill,
+        earth + dir * hill,
+        sun - dir * r * (1.0 + 5.0 * mu / 12.0),
+        around(PI / 3.0), // orbits run +Y (counter-clockwise from above), so +60° leads
+        around(-PI / 3.0),
+    ]
+}
+
+/// System: draws small gizmo markers at the Sun–Earth Lagrange points, from the bodies'
+/// current world positions (so circular, elliptical, and physics modes all track).
+/// The mass ratio comes from the scene `Mass` values; with the real μ ≈ 3·10⁻⁶, L1/L2
+/// would sit inside Earth's exaggerated sphere.
+pub(crate) fn draw_lagrange_points(
+    mut gizmos: Gizmos,
+    sun_q: Query<(&GlobalTransform, &Mass), With<Sun>>,
+    earth_q: Query<(&GlobalTransform, &Mass), With<Earth>>,
+    params: Res<SimulationParams>,
+) {
+    if !params.show_lagrange {
+        return;
+    }
+    let (Ok((sun, sun_mass)), Ok((earth, earth_mass))) = (sun_q.get_single(), earth_q.get_single()) else { return };
+    let mu = earth_mass.0 / (sun_mass.0 + earth_mass.0);
+    for point in lagrange_points(sun.translation(), earth.translation(), mu) {
+        gizmos.sphere(point, Quat::IDENTITY, 0.08, css::LIME);
+    }
+}
+
+/// Gizmo group: markers drawn through the bodies (negative depth bias), for points that
+/// sit inside a sphere.
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub(crate) struct XRayGizmos;
+
+/// System: marks the Earth–Moon barycenter on the line between them. With the scene
+/// masses it sits inside Earth's sphere, so it is drawn with `XRayGizmos`.
+pub(crate) fn draw_barycenter(
+    mut gizmos: Gizmos<XRayGizmos>,
+    earth_q: Query<(&GlobalTransform, &Mass), With<Earth>>,
+    moon_q: Query<(&GlobalTransform, &Mass), With<Moon>>,
+    params: Res<SimulationParams>,
+) {
+    if !params.show_barycenter {
+        return;
+    }
+    let (Ok((earth, earth_mass)), Ok((moon, moon_mass))) = (earth_q.get_single(), moon_q.get_single()) else { return };
+    let (earth, moon) = (earth.translation(), moon.translation());
+    let center = barycenter(earth, earth_mass.0, moon, moon_mass.0);
+    gizmos.line(earth, moon, css::ORANGE.with_alpha(0.3));
+    gizmos.sphere(center, Quat::IDENTITY, 0.05, css::ORANGE);
+    for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+        gizmos.line(center - axis * 0.1, center + axis * 0.1, css::ORANGE);
+    }
+}
+
+/// System: draws an arrow from each body along its world velocity, `velocity_arrow_scale`
+/// long per unit of speed. Physics mode uses the integrated `Velocity`; otherwise the
+/// `track_motion` estimate, so Earth's arrow stays tangent to its orbit and stretches near
+/// perihelion in elliptical mode.
+pub(crate) fn draw_velocity_arrows(
+    mut gizmos: Gizmos,
+    q: Query<(&GlobalTransform, &TrackedMotion, Option<&Velocity>)>,
+    params: Res<SimulationParams>,
+) {
+    if !params.show_velocity_arrows {
+        return;
+    }
+    for (global, motion, velocity) in &q {
+        let velocity = velocity.map_or(motion.velocity, |v| v.0);
+        if velocity.length_squared() < 1e-6 {
+            continue; // paused (or the Sun at rest): no zero-length arrows
+        }
+        let start = global.translation();
+        gizmos.arrow(start, start + velocity * params.velocity_arrow_scale, css::AQUA);
+    }
+}
@@ -0,0 +1,568 @@
+//! Simulation parameters, the sim clock, and the physical constants behind the default rates.
+
+use bevy::color::{palettes::css, LinearRgba};
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use crate::{eclipse::*, orbits::*, scene::*};
+
+/// Resource: simulation-wide tunables available to any system.
+/// In ECS, Resources are global singletons that systems can read/mutate.
+/// Serialized into save files; missing fields fall back to `Default`.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct SimulationParams {
+    pub(crate) time_scale: f64,           // simulated seconds per real second, before the speed sliders
+    pub(crate) orbit_speed_scale: f32,    // scales all orbital angular speeds (negative = rewind)
+    pub(crate) planet_orbit_scale: f32,   // extra factor for orbits around the Sun (planets, belt, comet)
+    pub(crate) moon_orbit_scale: f32,     // extra factor for the Moon's orbit around Earth
+    pub(crate) moon_resonance: bool,      // lock the Moon's base period to a ratio of one of Earth's (`apply_moon_resonance`)
+    pub(crate) resonance_target: ResonanceTarget, // which Earth period the ratio is taken against
+    pub(crate) resonance_ratio: [u32; 2], // Earth periods : Moon orbits, e.g. 1:12 = twelve orbits a year
+    pub(crate) spin_speed_scale: f32,     // scales all self-rotation angular speeds (negative = rewind)
+    pub(crate) log_sliders: bool,         // logarithmic speed/radius sliders, for finer control near zero
+    pub(crate) earth_orbit_radius: f32,   // Sun–Earth distance
+    pub(crate) moon_orbit_radius: f32,    // Earth–Moon distance
+    pub(crate) smooth_radius_changes: bool, // glide to new orbit radii instead of snapping
+    pub(crate) clamp_radii: bool,         // hold the radius sliders at their minimum safe separations
+    pub(crate) earth_start_phase_deg: f32, // Earth's angle along its orbit at t = 0, measured from +X
+    pub(crate) moon_start_phase_deg: f32, // Moon's angle around Earth at t = 0
+    pub(crate) planet_start_phases_deg: [f32; PLANETS.len()], // the other planets' angles at t = 0, in `PLANETS` order
+    pub(crate) elliptical_planets: [bool; PLANETS.len()], // per planet, follow its real-eccentricity `EllipticalOrbit`
+    pub(crate) planet_orbit_radii: [f32; PLANETS.len()], // per planet, circle radius / semi-major axis (scene units)
+    pub(crate) planet_speed_scales: [f32; PLANETS.len()], // per planet, factor on its real orbital rate
+    pub(crate) moon_inclination: f32,     // Moon orbit tilt to the ecliptic (rad), line of nodes along world X
+    pub(crate) tidally_locked: bool,      // Moon keeps one face toward Earth instead of using its `Spin`
+    pub(crate) axial_precession: bool,    // slowly turn Earth's tilted axis about the vertical
+    pub(crate) precession_deg_per_year: f32, // precession rate per simulated year (real Earth: 360° per ~26,000)
+    pub(crate) use_elliptical_orbit: bool, // when true, Earth follows its `EllipticalOrbit` instead of pivot rotation
+    pub(crate) use_kepler_motion: bool,   // elliptical mode: solve Kepler's equation (false = uniform parametric θ)
+    pub(crate) earth_orbit_inclination_deg: f32, // tilt of Earth's ellipse out of the XZ plane, about its major axis
+    pub(crate) earth_eccentricity: f32,   // shape of Earth's ellipse; `earth_orbit_radius` is its semi-major axis
+    pub(crate) moon_elliptical: bool,     // Moon follows its `EllipticalOrbit` around Earth instead of pivot rotation
+    pub(crate) moon_eccentricity: f32,    // shape of the Moon's ellipse; `moon_orbit_radius` is its semi-major axis
+    pub(crate) show_trails: bool,         // draw recent world-space paths of trailed bodies
+    pub(crate) trail_length: usize,       // how many recent positions each trail keeps
+    pub(crate) trail_fade: bool,          // fade older trail points out to transparent
+    pub(crate) trail_fade_secs: f32,      // real seconds for a trail point to fade out (and be dropped)
+    pub(crate) trail_gradient: bool,      // color trails from `trail_gradient_start` to `_end` instead of per body
+    pub(crate) trail_gradient_start: [f32; 3], // sRGB color of the newest trail points
+    pub(crate) trail_gradient_end: [f32; 3], // sRGB color of the oldest trail points
+    pub(crate) paused: bool,              // freezes orbits, spins, and the simulation clock
+    pub(crate) eclipse_threshold_deg: f32, // max Sun–Earth–Moon misalignment still counted as an eclipse
+    pub(crate) eclipse_tint: bool,        // darken/redden the eclipsed body while an eclipse is active
+    pub(crate) auto_slow_mo: bool,        // slow the simulation down around Sun–Earth–Moon alignments
+    pub(crate) slow_mo_floor: f32,        // lowest time-dilation factor auto slow-mo eases down to
+    pub(crate) pause_at: AlignmentTarget, // pause automatically when this alignment is reached
+    pub(crate) show_labels: bool,         // floating name labels above bodies
+    pub(crate) hidden_bodies: Vec<String>, // names of bodies not drawn (still simulated), see `apply_body_visibility`
+    pub(crate) retrograde_bodies: Vec<String>, // names of bodies orbiting backward, see `apply_orbit_directions`
+    pub(crate) reference_frame: Option<String>, // body the view is centered on (None = Sun-centered world), see `apply_reference_frame`
+    pub(crate) frame_trails: bool,        // record trails relative to `reference_frame` instead of world space
+    pub(crate) show_perf_hud: bool,       // FPS / frame time / entity count overlay
+    pub(crate) show_help: bool,           // controls reference window (F1)
+    pub(crate) hum_muted: bool,           // silence the ambient hum (see `AMBIENT_HUM`)
+    pub(crate) hum_volume: f32,           // hum volume at 1× orbit speed and above (0–1)
+    pub(crate) realistic_scale: bool,     // size bodies by their true radii relative to the Sun
+    pub(crate) size_exaggeration: f32,    // realistic mode: multiplier on every non-Sun body
+    pub(crate) physics_mode: bool,        // integrate Sun/Earth/Moon under mutual gravity instead of kinematic orbits
+    pub(crate) gravitational_constant: f32, // G for `nbody_gravity`, in scene units
+    pub(crate) gravity_softening: f32,    // softening length ε for `nbody_gravity` (scene units), see `gravity_accelerations`
+    pub(crate) sun_mass: f32,             // `Mass` of the Sun (see `Mass` for why these aren't real ratios)
+    pub(crate) earth_mass: f32,           // `Mass` of Earth
+    pub(crate) moon_mass: f32,            // `Mass` of the Moon
+    pub(crate) mesh_detail: MeshDetail,   // sphere tessellation, applied live by `rebuild_body_meshes`
+    pub(crate) wireframe: bool,           // overlay every body's mesh edges (per body: the selection panel)
+    pub(crate) msaa: MsaaLevel,           // antialiasing samples, applied (or lowered) by `apply_msaa`
+    pub(crate) render_scale: f32,         // fraction of the window resolution the 3D scene renders at, see `apply_render_scale`
+    pub(crate) color_scheme: ColorScheme, // how planets are colored, applied by `apply_color_scheme`
+    pub(crate) bloom_intensity: f32,      // camera `BloomSettings.intensity` (0 = no glow)
+    pub(crate) shadows: bool,             // the Sun's light casts shadows (eclipse shadows, planets on their moons)
+    pub(crate) shadow_map_size: usize,    // `PointLightShadowMap` cube face size, one of `SHADOW_MAP_SIZES`
+    pub(crate) show_lagrange: bool,       // gizmo markers at the Sun–Earth L1–L5 points
+    pub(crate) show_barycenter: bool,     // gizmo marker at the Earth–Moon center of mass
+    pub(crate) barycentric_motion: bool,  // Earth circles the barycenter instead of sitting on its orbit (not in physics mode)
+    pub(crate) show_velocity_arrows: bool, // gizmo arrow along each body's world velocity
+    pub(crate) velocity_arrow_scale: f32, // arrow length per unit of speed (s)
+    pub(crate) debug_day_night: bool,     // unlit sunward tint + terminator circle on every body, to check the lighting
+    pub(crate) solar_day: bool,           // count Earth's day noon to noon (solar) rather than against the stars (sidereal)
+    pub(crate) show_day_markers: bool,    // meridian, Sun-direction, and fixed-star lines on Earth
+    pub(crate) camera_fov_deg: f32,       // vertical field of view of the perspective camera
+    pub(crate) camera_near: f32,          // near clip plane distance
+    pub(crate) camera_far: f32,           // far clip plane distance (keep past the starfield radius)
+    pub(crate) camera_inertia: bool,      // a flicked rotate drag keeps turning after release
+    pub(crate) camera_damping: f32,       // inertia decay rate (1/s); higher stops sooner
+    pub(crate) camera_roll_deg: f32,      // camera roll about its line of sight (°)
+    pub(crate) look_along_orbit: bool,    // ride alongside Earth, yaw/pitch measured from its direction of travel
+    pub(crate) cycle_follows: bool,       // Tab selection also points the follow-cam at the new body
+    pub(crate) fixed_timestep: bool,      // step orbits/spins/clock in `FixedUpdate` for frame-rate-independent runs
+    pub(crate) max_frame_dt: f32,         // cap on one frame's step (s), so a hitch can't teleport bodies
+    pub(crate) ambient_color: [f32; 3],   // sRGB ambient light color
+    pub(crate) ambient_brightness: f32,   // `AmbientLight.brightness`
+    pub(crate) sun_light_intensity: f32,  // Sun's point light power (lumens) at radius 1; falls off with distance²
+    pub(crate) sun_radius: f32,           // Sun sphere radius; its light scales with the surface area
+    pub(crate) fill_illuminance: f32,     // optional directional fill light illuminance (lux)
+    pub(crate) night_lights: bool,        // city lights on Earth's night side (see `EARTH_NIGHT_TEXTURE`); turned on once it loads
+    pub(crate) night_lights_strength: f32, // brightness multiplier for the city lights
+    pub(crate) fill_light_pitch_deg: f32, // fill light rotation about X
+    pub(crate) fill_light_yaw_deg: f32,   // fill light rotation about Y
+    pub(crate) background_color: [f32; 3], // sRGB clear color behind the scene
+    pub(crate) gradient_background: bool, // vertical gradient backdrop instead of the flat clear color
+    pub(crate) gradient_top: [f32; 3],    // sRGB color at the top of the view
+    pub(crate) gradient_bottom: [f32; 3], // sRGB color at the bottom of the view
+    pub(crate) belt_count: usize,         // number of asteroids; the belt respawns when these change
+    pub(crate) belt_inner_au: f32,        // asteroid belt inner edge (AU)
+    pub(crate) belt_outer_au: f32,        // asteroid belt outer edge (AU)
+    pub(crate) show_comet: bool,          // spawn the comet (despawned again when turned off)
+    pub(crate) extra_earth_moons: usize,  // fictional moons outside the real one; rebuilt by `sync_moons`
+    pub(crate) jupiter_moons: usize,      // moons of Jupiter (Galilean names first)
+    pub(crate) show_ecliptic_grid: bool,  // gizmo grid on the XZ plane through the Sun
+    pub(crate) grid_extent: f32,          // grid half-width (scene units)
+    pub(crate) grid_spacing: f32,         // distance between grid lines
+    pub(crate) show_orbit_circles: bool,  // each body's full orbit path: a circle, or its ellipse when riding one
+    pub(crate) orbit_path_samples: u32,   // points per drawn orbit path
+    pub(crate) orbit_path_color: [f32; 4], // sRGB + alpha of the orbit paths
+    pub(crate) show_nodes: bool,          // line of nodes and ascending/descending node markers on inclined orbits
+    pub(crate) show_retrograde: bool,     // plot Mars's direction as seen from Earth on the sky sphere
+    pub(crate) retrograde_spread: bool,   // lift older sky samples off the ecliptic so the backtrack shows as a loop
+    pub(crate) show_minimap: bool,        // top-down orthographic inset in the bottom-left corner
+}
+
+/// Sphere tessellation level for every body. UV spheres throughout, so textures wrap
+/// cleanly; High mostly matters up close or for the small outer planets when zoomed in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum MeshDetail {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl MeshDetail {
+    pub(crate) const ALL: [MeshDetail; 3] = [MeshDetail::Low, MeshDetail::Medium, MeshDetail::High];
+
+    /// (sectors around, stacks pole to pole)
+    pub(crate) fn subdivisions(self) -> (usize, usize) {
+        match self {
+            MeshDetail::Low => (16, 8),
+            MeshDetail::Medium => (64, 32),
+            MeshDetail::High => (160, 80),
+        }
+    }
+}
+
+/// The Earth period the Moon's is locked to in resonance mode.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum ResonanceTarget {
+    #[default]
+    EarthOrbit,    // the year
+    EarthRotation, // the sidereal day
+}
+
+impl ResonanceTarget {
+    pub(crate) const ALL: [ResonanceTarget; 2] = [ResonanceTarget::EarthOrbit, ResonanceTarget::EarthRotation];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            ResonanceTarget::EarthOrbit => "Earth years",
+            ResonanceTarget::EarthRotation => "Earth days",
+        }
+    }
+}
+
+/// Shadow map resolutions offered in the UI: texels per side of each cube face of the Sun's
+/// shadow map. Bigger is crisper (eclipse shadows) but costs six times its area in memory.
+pub(crate) const SHADOW_MAP_SIZES: [usize; 5] = [256, 512, 1024, 2048, 4096];
+
+/// MSAA sample count choice. Defaults to 4x, which is Bevy's own default `Msaa`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum MsaaLevel {
+    Off,
+    X2,
+    #[default]
+    X4,
+    X8,
+}
+
+impl MsaaLevel {
+    pub(crate) const ALL: [MsaaLevel; 4] = [MsaaLevel::Off, MsaaLevel::X2, MsaaLevel::X4, MsaaLevel::X8];
+
+    pub(crate) fn msaa(self) -> Msaa {
+        match self {
+            MsaaLevel::Off => Msaa::Off,
+            MsaaLevel::X2 => Msaa::Sample2,
+            MsaaLevel::X4 => Msaa::Sample4,
+            MsaaLevel::X8 => Msaa::Sample8,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            MsaaLevel::Off => "Off",
+            MsaaLevel::X2 => "2x",
+            MsaaLevel::X4 => "4x",
+            MsaaLevel::X8 => "8x",
+        }
+    }
+}
+
+/// Planet coloring. Natural uses each `Planet`'s own color; Temperature runs from warm
+/// (Mercury) to cool (Neptune) by log Sun distance; Grayscale keeps only the natural
+/// color's luminance, a rough albedo.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum ColorScheme {
+    #[default]
+    Natural,
+    Temperature,
+    Grayscale,
+}
+
+impl ColorScheme {
+    pub(crate) const ALL: [ColorScheme; 3] = [ColorScheme::Natural, ColorScheme::Temperature, ColorScheme::Grayscale];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            ColorScheme::Natural => "Natural",
+            ColorScheme::Temperature => "Temperature",
+            ColorScheme::Grayscale => "Grayscale",
+        }
+    }
+
+    /// Base color for `planet` under this scheme.
+    pub(crate) fn color(self, planet: &Planet) -> Color {
+        match self {
+            ColorScheme::Natural => planet.color,
+            ColorScheme::Temperature => {
+                let (inner, outer) = (PLANETS[0].orbit_au, PLANETS[PLANETS.len() - 1].orbit_au);
+                let t = ((planet.orbit_au / inner).ln() / (outer / inner).ln()).clamp(0.0, 1.0);
+                let (warm, cool) = (Vec3::new(1.0, 0.4, 0.15), Vec3::new(0.3, 0.55, 1.0));
+                let rgb = warm.lerp(cool, t);
+                Color::srgb(rgb.x, rgb.y, rgb.z)
+            }
+            ColorScheme::Grayscale => {
+                // Relative luminance, from linear RGB
+                let c = planet.color.to_linear();
+                let y = 0.2126 * c.red + 0.7152 * c.green + 0.0722 * c.blue;
+                Color::linear_rgb(y, y, y)
+            }
+        }
+    }
+}
+
+/// Allowed range for `orbit_speed_scale`/`spin_speed_scale`, shared by sliders and keys.
+/// Negative values run the simulation backward.
+pub(crate) const SPEED_SCALE_RANGE: RangeInclusive<f32> = -5.0..=5.0;
+
+/// Slider ranges for the Sun–Earth and Earth–Moon distances. `detect_overlaps` never
+/// clamps past the top end, so the slider doesn't fight it.
+pub(crate) const EARTH_ORBIT_RADIUS_RANGE: RangeInclusive<f32> = 1.0..=10.0;
+pub(crate) const MOON_ORBIT_RADIUS_RANGE: RangeInclusive<f32> = 0.2..=3.0;
+/// Slider ranges for each planet's row in the "Orbits" section: its radius (the table
+/// default puts Neptune near 15) and the factor on its real orbital rate.
+pub(crate) const PLANET_ORBIT_RADIUS_RANGE: RangeInclusive<f32> = 0.5..=20.0;
+pub(crate) const PLANET_SPEED_RANGE: RangeInclusive<f32> = 0.0..=10.0;
+
+impl Default for SimulationParams {
+    fn default() -> Self {
+        Self {
+            time_scale: SECONDS_PER_DAY,
+            orbit_speed_scale: 1.0,
+            planet_orbit_scale: 1.0,
+            moon_orbit_scale: 1.0,
+            moon_resonance: false,
+            resonance_target: ResonanceTarget::EarthOrbit,
+            resonance_ratio: [1, 12],
+            spin_speed_scale: 1.0,
+            log_sliders: false,
+            earth_orbit_radius: 3.0,
+            moon_orbit_radius: 0.9,
+            smooth_radius_changes: true,
+            clamp_radii: false,
+            earth_start_phase_deg: 0.0,
+            moon_start_phase_deg: 0.0,
+            planet_start_phases_deg: [0.0; PLANETS.len()],
+            elliptical_planets: [false; PLANETS.len()],
+            planet_orbit_radii: PLANETS.map(|planet| scene_orbit_radius(planet.orbit_au)),
+            planet_speed_scales: [1.0; PLANETS.len()],
+            moon_inclination: 5.14_f32.to_radians(),
+            tidally_locked: false,
+            axial_precession: false,
+            precession_deg_per_year: 10.0,
+            use_elliptical_orbit: false,
+            use_kepler_motion: true,
+            earth_orbit_inclination_deg: 0.0,
+            earth_eccentricity: 0.3, // exaggerated (real: 0.017) so the speed-up is visible
+            moon_elliptical: false,
+            moon_eccentricity: 0.2, // exaggerated (real: 0.055) so perigee and apogee stand out
+            show_trails: true,
+            trail_length: 512,
+            trail_fade: true,
+            trail_fade_secs: 8.0,
+            trail_gradient: false,
+            trail_gradient_start: [1.0, 0.95, 0.7],
+            trail_gradient_end: [0.2, 0.4, 1.0],
+            paused: false,
+            eclipse_threshold_deg: 2.0,
+            eclipse_tint: true,
+            auto_slow_mo: false,
+            slow_mo_floor: 0.1,
+            pause_at: AlignmentTarget::Off,
+            show_labels: true,
+            hidden_bodies: Vec::new(),
+            retrograde_bodies: Vec::new(),
+            reference_frame: None,
+            frame_trails: true,
+            show_perf_hud: false,
+            show_help: false,
+            hum_muted: false,
+            hum_volume: 0.3,
+            realistic_scale: false,
+            size_exaggeration: 20.0,
+            physics_mode: false,
+            gravitational_constant: 1.0,
+            gravity_softening: 0.1,
+            sun_mass: 2.0,
+            earth_mass: 0.8,
+            moon_mass: 0.01,
+            mesh_detail: MeshDetail::Medium,
+            wireframe: false,
+            msaa: MsaaLevel::default(),
+            render_scale: 1.0,
+            color_scheme: ColorScheme::Natural,
+            shadows: true,
+            shadow_map_size: 1024, // Bevy's default
+            bloom_intensity: BloomSettings::NATURAL.intensity,
+            show_lagrange: false,
+            show_barycenter: false,
+            barycentric_motion: false,
+            show_velocity_arrows: false,
+            velocity_arrow_scale: 0.5,
+            debug_day_night: false,
+            solar_day: false,
+            show_day_markers: false,
+            // Match Bevy's `PerspectiveProjection::default()` so nothing changes until touched
+            camera_fov_deg: PerspectiveProjection::default().fov.to_degrees(),
+            camera_near: PerspectiveProjection::default().near,
+            camera_far: PerspectiveProjection::default().far,
+            camera_inertia: true,
+            camera_damping: 4.0,
+            camera_roll_deg: 0.0,
+            look_along_orbit: false,
+            cycle_follows: false,
+            fixed_timestep: false,
+            max_frame_dt: 1.0 / 30.0,
+            ambient_color: [0.1, 0.1, 0.2],
+            ambient_brightness: 0.5,
+            // ≈ 60 000 lux at Earth's distance (I / 4πr², r = 3), the old directional level
+            sun_light_intensity: 6.8e6,
+            sun_radius: 1.0,
+            fill_illuminance: 0.0,
+            night_lights: false,
+            night_lights_strength: 1.0,
+            fill_light_pitch_deg: -45.0,
+            fill_light_yaw_deg: -45.0,
+            background_color: [0.0, 0.0, 0.0],
+            gradient_background: false,
+            gradient_top: [0.55, 0.7, 0.95],
+            gradient_bottom: [0.95, 0.96, 1.0],
+            belt_count: 300,
+            belt_inner_au: 2.2,
+            belt_outer_au: 3.2,
+            show_comet: false,
+            extra_earth_moons: 0,
+            jupiter_moons: 0,
+            show_ecliptic_grid: false,
+            show_nodes: false,
+            grid_extent: 20.0,
+            grid_spacing: 1.0,
+            show_orbit_circles: false,
+            orbit_path_samples: 128,
+            orbit_path_color: [0.6, 0.7, 0.9, 0.25],
+            show_retrograde: false,
+            retrograde_spread: true,
+            show_minimap: false,
+        }
+    }
+}
+
+impl SimulationParams {
+    /// Starting phase (rad) of one orbit at t = 0, from the per-body "Start phases"
+    /// sliders (stored in degrees): the pivot's yaw, or the eccentric anomaly on an ellipse.
+    pub(crate) fn start_phase(&self, target: PhaseTarget) -> f32 {
+        match target {
+            PhaseTarget::Earth => self.earth_start_phase_deg,
+            PhaseTarget::Moon => self.moon_start_phase_deg,
+            PhaseTarget::Planet(index) => self.planet_start_phases_deg[index],
+        }
+        .to_radians()
+    }
+
+    /// Base orbital rate (rad/s, unsigned) of the planet at `index` in `PLANETS`: its real
+    /// period's rate times its "Orbits" speed slider.
+    pub(crate) fn planet_rate(&self, index: usize) -> f32 {
+        rate_for_period(PLANETS[index].orbit_period_days.into()) * self.planet_speed_scales[index]
+    }
+
+    /// Effective orbit-speed multiplier: the global scale times the Moon's or the
+    /// heliocentric factor, depending on which pivot is being driven.
+    pub(crate) fn orbit_scale(&self, is_moon_pivot: bool) -> f32 {
+        self.orbit_speed_scale * if is_moon_pivot { self.moon_orbit_scale } else { self.planet_orbit_scale }
+    }
+
+    /// Body whose frame trails are recorded in, if any: `reference_frame` unless
+    /// `frame_trails` is off.
+    pub(crate) fn trail_frame(&self) -> Option<&str> {
+        self.reference_frame.as_deref().filter(|_| self.frame_trails)
+    }
+
+    /// This frame's step (s) for the simulation systems, before the speed sliders: the
+    /// frame time capped at `max_frame_dt`, so a lag spike (dragging the window, a loading
+    /// stall) only costs a little sim time instead of swinging bodies round their orbits,
+    /// then slowed by auto slow-mo. Zero while the clock is `suspended`.
+    pub(crate) fn step_dt(&self, time: &Time, clock: &SimClock) -> f32 {
+        if clock.suspended {
+            return 0.0;
+        }
+        time.delta_seconds().min(self.max_frame_dt) * clock.time_dilation
+    }
+
+    /// `step_dt` in simulated seconds (× `time_scale`), for advancing the kinematic angles,
+    /// whose rates come from real periods. f64, as a frame can span days.
+    pub(crate) fn sim_dt(&self, time: &Time, clock: &SimClock) -> f64 {
+        f64::from(self.step_dt(time, clock)) * self.time_scale
+    }
+
+    /// The `AmbientLight` resource these params describe.
+    pub(crate) fn ambient_light(&self) -> AmbientLight {
+        let [r, g, b] = self.ambient_color;
+        AmbientLight {
+            color: Color::srgb(r, g, b),
+            brightness: self.ambient_brightness,
+        }
+    }
+
+    /// The `ClearColor` resource these params describe.
+    pub(crate) fn clear_color(&self) -> ClearColor {
+        let [r, g, b] = self.background_color;
+        ClearColor(Color::srgb(r, g, b))
+    }
+
+    /// The Sun's point-light power: a sphere at a fixed surface brightness radiates in
+    /// proportion to its area, so a bigger Sun is brighter by `sun_radius²`.
+    pub(crate) fn sun_light_power(&self) -> f32 {
+        self.sun_light_intensity * self.sun_radius * self.sun_radius
+    }
+
+    /// The Sun material's glow. Grows with `sun_radius` too, but only linearly so the bloom
+    /// doesn't wash out the whole frame.
+    pub(crate) fn sun_emissive(&self) -> LinearRgba {
+        LinearRgba::from(css::ORANGE) * 20.0 * self.sun_radius
+    }
+
+    /// World rotation of the directional fill light.
+    pub(crate) fn fill_light_rotation(&self) -> Quat {
+        Quat::from_euler(
+            EulerRot::XYZ,
+            self.fill_light_pitch_deg.to_radians(),
+            self.fill_light_yaw_deg.to_radians(),
+            0.0,
+        )
+    }
+}
+
+/// Run condition: the kinematic stepping systems live in both `FixedUpdate` and `Update`;
+/// this picks which copy runs. With a fixed step, Bevy's `Time<Fixed>` carries leftover
+/// frame time over to the next frame, so the same sim time always takes the same number
+/// of identical steps regardless of render FPS (at the cost of slight judder, as there's
+/// no interpolation between steps).
+pub(crate) fn fixed_timestep(params: Res<SimulationParams>) -> bool {
+    params.fixed_timestep
+}
+
+/// Resource: simulated time elapsed so far, advanced by `tick_clock`.
+/// Scaled by `time_scale`, `orbit_speed_scale` (and auto slow-mo) and frozen while paused, so it tracks the orbits.
+#[derive(Resource)]
+pub(crate) struct SimClock {
+    pub(crate) sim_seconds: f64,       // simulated seconds
+    pub(crate) earth_orbit_angle: f64, // Earth's accumulated orbital angle (rad); 2π = one simulated year
+    pub(crate) time_dilation: f32,     // auto slow-mo factor on top of the speed sliders (1 = full speed)
+    pub(crate) suspended: bool,        // window minimized: nothing steps (see `suspend_while_minimized`)
+}
+
+impl Default for SimClock {
+    fn default() -> Self {
+        Self { sim_seconds: 0.0, earth_orbit_angle: 0.0, time_dilation: 1.0, suspended: false }
+    }
+}
+
+/// Simulated seconds per day, the unit of `time_scale` presets and the periods below.
+pub(crate) const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Real periods (days) behind the kinematic rates; see `rate_for_period`.
+pub(crate) const EARTH_YEAR_DAYS: f64 = 365.256; // sidereal year
+pub(crate) const EARTH_DAY_DAYS: f64 = 0.99727; // sidereal day
+pub(crate) const MOON_MONTH_DAYS: f64 = 27.3217; // sidereal month
+pub(crate) const SUN_ROTATION_DAYS: f64 = 25.38; // at the equator
+
+/// Earth's axial tilt (obliquity), the angle between its spin axis and the ecliptic normal.
+pub(crate) const EARTH_AXIAL_TILT_DEG: f32 = 23.44;
+
+/// Angular speed (rad per simulated second) of something turning once every `days`.
+pub(crate) fn rate_for_period(days: f64) -> f32 {
+    (std::f64::consts::TAU / (days * SECONDS_PER_DAY)) as f32
+}
+
+/// A simulated span for the UI, in the largest unit that keeps it ≥ 1 (minutes → years).
+pub(crate) fn format_duration(seconds: f64) -> String {
+    const UNITS: [(f64, &str); 5] = [
+        (365.25 * SECONDS_PER_DAY, "y"),
+        (30.44 * SECONDS_PER_DAY, "mo"),
+        (SECONDS_PER_DAY, "d"),
+        (3600.0, "h"),
+        (60.0, "min"),
+    ];
+    let (unit, suffix) = UNITS.into_iter().find(|&(unit, _)| seconds.abs() >= unit).unwrap_or((1.0, "s"));
+    format!("{:.2} {suffix}", seconds / unit)
+}
+
+/// `time_scale` buttons in `ui_panel`: (label, simulated seconds per real second).
+pub(crate) const TIME_SCALE_PRESETS: [(&str, f64); 4] = [
+    ("1 hour/s", 3600.0),
+    ("1 day/s", SECONDS_PER_DAY),
+    ("1 month/s", 30.44 * SECONDS_PER_DAY),
+    ("1 year/s", 365.25 * SECONDS_PER_DAY),
+];
+
+impl SimClock {
+    pub(crate) const DAYS_PER_YEAR: f64 = 365.25;
+
+    /// Simulated years elapsed, derived from Earth's accumulated orbital angle.
+    pub(crate) fn years(&self) -> f64 {
+        self.earth_orbit_angle / std::f64::consts::TAU
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn huge_frame_dt_is_clamped() {
+        let params = SimulationParams::default();
+        let mut clock = SimClock::default();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs(1_000_000_000)); // a ~32-year hitch
+        let dt = params.step_dt(&time, &clock);
+        assert_eq!(dt, params.max_frame_dt);
+        // An orbit advanced by that step stays a finite angle in [0, 2π)
+        let rate = f64::from(rate_for_period(MOON_MONTH_DAYS) * params.orbit_scale(true));
+        let yaw = (1.0 + rate * params.sim_dt(&time, &clock)).rem_euclid(std::f64::consts::TAU);
+        assert!(yaw.is_finite() && (0.0..std::f64::consts::TAU).contains(&yaw), "yaw {yaw}");
+        let max_swing = rate * f64::from(params.max_frame_dt) * params.time_scale;
+        assert!(max_swing < 0.1, "one capped step turns the Moon {max_swing} rad");
+        clock.suspended = true;
+        assert_eq!(params.step_dt(&time, &clock), 0.0);
+    }
+}
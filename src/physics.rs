@@ -0,0 +1,331 @@
+//! N-body gravity mode and the orbital elements it reads off.
+
+use bevy::ecs::system::SystemParam;
+use bevy::math::Affine3A;
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+use crate::{params::*, scene::*};
+
+/// Resource: names of physics-mode bodies moving faster than escape speed from their
+/// primary (hyperbolic), with that primary's name, from `detect_unbound`.
+#[derive(Resource, Default, PartialEq)]
+pub(crate) struct UnboundBodies(pub(crate) Vec<(String, String)>);
+
+/// Event: put the physics-mode bodies back on circular orbits at the slider radii, with
+/// velocities reseeded for the current masses and `G` (the "Reset to stable circular" button).
+#[derive(Event)]
+pub(crate) struct CircularizeOrbits;
+
+/// Component: gravitating mass in scene units, used by `nbody_gravity` in physics mode.
+/// Not real ratios: at these compressed distances a real Earth/Sun ratio would put the Moon
+/// far outside Earth's Hill sphere, so Earth is made heavy enough to hold on to it.
+#[derive(Component)]
+pub(crate) struct Mass(pub(crate) f32);
+
+/// Component: world-space velocity (scene units/s) of a gravitating body. Inserted by
+/// `nbody_gravity` when physics mode starts and removed when it stops.
+#[derive(Component)]
+pub(crate) struct Velocity(pub(crate) Vec3);
+
+/// System: copies the mass sliders onto the bodies' `Mass` components, so a change takes
+/// effect on the very next gravity step (and in the Lagrange-point mass ratio). Only the
+/// Sun, Earth, and the Moon carry a `Mass`.
+pub(crate) fn apply_masses(mut q: Query<(&mut Mass, Has<Sun>, Has<Moon>)>, params: Res<SimulationParams>) {
+    for (mut mass, is_sun, is_moon) in &mut q {
+        let target = match (is_sun, is_moon) {
+            (true, _) => params.sun_mass,
+            (_, true) => params.moon_mass,
+            _ => params.earth_mass,
+        };
+        if mass.0 != target {
+            mass.0 = target;
+        }
+    }
+}
+
+/// Query data for a body `detect_unbound` checks.
+type BoundCheckBody = (Entity, &'static Name, &'static Mass, &'static GlobalTransform, &'static Velocity, Has<Sun>, Has<Moon>);
+
+/// System: physics mode. Flags each body whose specific orbital energy relative to its
+/// primary (the Moon's is Earth, everyone else's the Sun), v²/2 − G(M + m)/√(r² + ε²) with
+/// the same softening as `gravity_accelerations`, is not negative: it's on a parabolic or
+/// hyperbolic path and won't come back.
+pub(crate) fn detect_unbound(
+    bodies: Query<BoundCheckBody>,
+    earth_q: Query<Entity, With<Earth>>,
+    mut unbound: ResMut<UnboundBodies>,
+    params: Res<SimulationParams>,
+) {
+    let g = params.gravitational_constant;
+    let sun = bodies.iter().find(|b| b.5);
+    let earth = earth_q.get_single().ok().and_then(|e| bodies.get(e).ok());
+    let mut escaping = Vec::new();
+    for (entity, name, mass, global, velocity, is_sun, is_moon) in &bodies {
+        let primary = if is_moon { earth } else { sun };
+        let Some((primary_entity, primary_name, primary_mass, primary_global, primary_velocity, ..)) = primary else { continue };
+        if is_sun || entity == primary_entity {
+            continue;
+        }
+        let r_sq = global.translation().distance_squared(primary_global.translation());
+        let r = (r_sq + params.gravity_softening * params.gravity_softening).sqrt().max(1e-3);
+        let v = velocity.0 - primary_velocity.0;
+        if 0.5 * v.length_squared() - g * (primary_mass.0 + mass.0) / r >= 0.0 {
+            escaping.push((name.to_string(), primary_name.to_string()));
+        }
+    }
+    unbound.set_if_neq(UnboundBodies(escaping));
+}
+
+/// Gravitational acceleration on each of `bodies` (position, mass) from all the others,
+/// Plummer-softened: r² + ε² replaces r² in the denominator, so a close pass peaks at a
+/// finite pull instead of flinging both bodies off. The price is accuracy: each body acts
+/// like a mass smeared over about `softening`, so orbits much wider than it change little
+/// (at the default ε Earth's pull on the Moon is ~2% weaker, the Sun's on Earth ~0.2%),
+/// while tighter ones and near-collisions come out weaker than real gravity. Too small an
+/// ε stops helping once a body covers more than about ε per substep at closest approach.
+/// ε = 0 is plain Newtonian gravity.
+fn gravity_accelerations(bodies: &[(Vec3, f32)], g: f32, softening: f32) -> Vec<Vec3> {
+    bodies
+        .iter()
+        .enumerate()
+        .map(|(i, &(pos, _))| {
+            bodies
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, &(other, mass))| {
+                    let offset = other - pos;
+                    let dist_sq = (offset.length_squared() + softening * softening).max(1e-6);
+                    offset * (g * mass / (dist_sq * dist_sq.sqrt()))
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// One velocity Verlet (kick–drift–kick) step of length `h` for `(position, velocity, mass)`
+/// bodies under `gravity_accelerations`.
+fn verlet_step(bodies: &mut [(Vec3, Vec3, f32)], g: f32, softening: f32, h: f32) {
+    let masses: Vec<_> = bodies.iter().map(|&(pos, _, mass)| (pos, mass)).collect();
+    for (body, acc) in bodies.iter_mut().zip(gravity_accelerations(&masses, g, softening)) {
+        body.1 += acc * (0.5 * h);
+        body.0 += body.1 * h;
+    }
+    let masses: Vec<_> = bodies.iter().map(|&(pos, _, mass)| (pos, mass)).collect();
+    for (body, acc) in bodies.iter_mut().zip(gravity_accelerations(&masses, g, softening)) {
+        body.1 += acc * (0.5 * h);
+    }
+}
+
+/// Query data for a gravitating body; `Velocity` is absent until physics mode seeds it.
+type GravityBody = (Entity, &'static Mass, &'static GlobalTransform, Option<&'static mut Velocity>, Has<Sun>, Has<Moon>);
+
+/// The hierarchy `nbody_gravity` writes positions back through, plus Earth and the Moon's
+/// orbit plane, which set the frame the Moon is seeded and circularized in.
+#[derive(SystemParam)]
+pub(crate) struct GravityFrames<'w, 's> {
+    transforms: Query<'w, 's, (&'static mut Transform, Option<&'static Parent>)>,
+    earth_q: Query<'w, 's, Entity, With<Earth>>,
+    plane_q: Query<'w, 's, &'static GlobalTransform, With<MoonOrbitPlane>>,
+}
+
+/// System: physics mode. Steps every `Mass` body with `verlet_step` in world space and
+/// writes the results back through the pivots above it, seeding circular velocities first
+/// (again after a `CircularizeOrbits`, from the slider radii). See the README for caveats.
+pub(crate) fn nbody_gravity(
+    mut commands: Commands,
+    mut circularize: EventReader<CircularizeOrbits>,
+    mut bodies: Query<GravityBody>,
+    frames: GravityFrames,
+    time: Res<Time>,
+    clock: Res<SimClock>,
+    params: Res<SimulationParams>,
+) {
+    const MAX_SUBSTEP: f32 = 1.0 / 120.0;
+    let GravityFrames { mut transforms, earth_q, plane_q } = frames;
+
+    let reseed = circularize.read().count() > 0;
+    if !params.physics_mode || reseed {
+        for (entity, _, _, velocity, _, _) in &bodies {
+            if velocity.is_some() {
+                commands.entity(entity).remove::<Velocity>();
+            }
+        }
+    }
+    if !params.physics_mode {
+        return;
+    }
+    if reseed {
+        let sun = bodies.iter().find(|b| b.4).map(|b| b.2.translation());
+        let earth = earth_q.get_single().ok().and_then(|e| bodies.get(e).ok()).map(|b| (b.0, b.2.translation()));
+        let moon = bodies.iter().find(|b| b.5).map(|b| (b.0, b.2.translation()));
+        let moon_normal = plane_q.get_single().map_or(Vec3::Y, |plane| *plane.up());
+        let mut targets = Vec::new();
+        if let (Some(sun), Some((earth_entity, earth_pos))) = (sun, earth) {
+            // Earth back onto the ecliptic at the slider radius, the Moon into its plane
+            let out = (earth_pos - sun).reject_from(Vec3::Y).try_normalize().unwrap_or(Vec3::X);
+            let new_earth = sun + out * params.earth_orbit_radius;
+            targets.push((earth_entity, new_earth));
+            if let Some((moon_entity, moon_pos)) = moon {
+                let out = (moon_pos - earth_pos).reject_from(moon_normal).try_normalize().unwrap_or(Vec3::X);
+                targets.push((moon_entity, new_earth + out * params.moon_orbit_radius));
+            }
+        }
+        set_world_translations(&mut transforms, targets);
+        return;
+    }
+
+    // Seed velocities for bodies that don't have one yet (physics just turned on, or reset)
+    if bodies.iter().any(|(_, _, _, velocity, _, _)| velocity.is_none()) {
+        let g = params.gravitational_constant;
+        let earth_entity = earth_q.get_single().ok();
+        let Some((sun_mass, sun_pos)) = bodies.iter().find(|b| b.4).map(|b| (b.1 .0, b.2.translation())) else {
+            return;
+        };
+        let earth = earth_entity.and_then(|e| bodies.get(e).ok()).map(|b| (b.1 .0, b.2.translation()));
+        let moon_normal = plane_q.get_single().map_or(Vec3::Y, |plane| *plane.up());
+        // Circular speed around a primary under the softened pull, prograde about `normal`
+        // like the `Orbit` pivots: v² = G(M + m)·r² / (r² + ε²)^(3/2)
+        let circular = |primary_mass: f32, primary_pos: Vec3, mass: f32, pos: Vec3, normal: Vec3| {
+            let offset = pos - primary_pos;
+            let r_sq = offset.length_squared().max(1e-6);
+            let soft_sq = r_sq + params.gravity_softening * params.gravity_softening;
+            let speed = (g * (primary_mass + mass) * r_sq / (soft_sq * soft_sq.sqrt())).sqrt();
+            normal.cross(offset).normalize_or_zero() * speed
+        };
+        let earth_velocity = earth.map_or(Vec3::ZERO, |(mass, pos)| circular(sun_mass, sun_pos, mass, pos, Vec3::Y));
+        // Velocities relative to a resting Sun first, then shift into the barycentric frame
+        let (mut momentum, mut total_mass) = (Vec3::ZERO, 0.0);
+        let mut seeded = Vec::new();
+        for (entity, mass, global, _, is_sun, is_moon) in &bodies {
+            let pos = global.translation();
+            let velocity = match earth {
+                _ if is_sun => Vec3::ZERO,
+                _ if Some(entity) == earth_entity => earth_velocity,
+                Some((earth_mass, earth_pos)) if is_moon => {
+                    earth_velocity + circular(earth_mass, earth_pos, mass.0, pos, moon_normal)
+                }
+                _ => circular(sun_mass, sun_pos, mass.0, pos, Vec3::Y),
+            };
+            momentum += velocity * mass.0;
+            total_mass += mass.0;
+            seeded.push((entity, velocity));
+        }
+        for (entity, velocity) in seeded {
+            commands.entity(entity).insert(Velocity(velocity - momentum / total_mass));
+        }
+        return;
+    }
+    if params.paused {
+        return;
+    }
+
+    // Gather world-space state, then step with kick–drift–kick
+    let (entities, mut state): (Vec<_>, Vec<_>) = bodies
+        .iter()
+        .map(|(entity, mass, global, velocity, _, _)| (entity, (global.translation(), velocity.map_or(Vec3::ZERO, |v| v.0), mass.0)))
+        .unzip();
+    // G and the masses are scene units with no real periods behind them, so this steps in
+    // real seconds: `time_scale` only applies to the kinematic orbits
+    let dt = params.step_dt(&time, &clock) * params.orbit_speed_scale;
+    let steps = (dt.abs() / MAX_SUBSTEP).ceil().max(1.0) as usize;
+    let h = dt / steps as f32;
+    for _ in 0..steps {
+        verlet_step(&mut state, params.gravitational_constant, params.gravity_softening, h);
+    }
+
+    set_world_translations(&mut transforms, entities.iter().zip(&state).map(|(&entity, &(pos, ..))| (entity, pos)).collect());
+    for (entity, (_, velocity, _)) in entities.into_iter().zip(state) {
+        if let Ok((.., Some(mut v), _, _)) = bodies.get_mut(entity) {
+            v.0 = velocity;
+        }
+    }
+}
+
+/// Moves each entity to a world-space position by rewriting its local translation through
+/// the transforms above it. Shallowest first, so a child (Moon) sees its parent's (Earth's)
+/// new place.
+fn set_world_translations(transforms: &mut Query<(&mut Transform, Option<&Parent>)>, mut targets: Vec<(Entity, Vec3)>) {
+    let parent_world = |transforms: &Query<(&mut Transform, Option<&Parent>)>, entity: Entity| {
+        let mut world = Affine3A::IDENTITY;
+        let mut depth = 0;
+        let mut next = transforms.get(entity).ok().and_then(|(_, parent)| parent.map(Parent::get));
+        while let Some((transform, up)) = next.and_then(|e| transforms.get(e).ok()) {
+            world = transform.compute_affine() * world;
+            next = up.map(Parent::get);
+            depth += 1;
+        }
+        (world, depth)
+    };
+    targets.sort_by_cached_key(|&(entity, _)| parent_world(transforms, entity).1);
+    for (entity, pos) in targets {
+        let (world, _) = parent_world(transforms, entity);
+        if let Ok((mut transform, _)) = transforms.get_mut(entity) {
+            transform.translation = world.inverse().transform_point3(pos);
+        }
+    }
+}
+
+/// Classical orbital elements of a body about its primary, as `selection_panel` shows them.
+pub(crate) struct OrbitalElements {
+    pub(crate) semi_major_axis: f32, // scene units; negative for an unbound (hyperbolic) path
+    pub(crate) eccentricity: f32,
+    pub(crate) inclination: f32,     // rad between the orbit's normal and the ecliptic's (+Y); > π/2 is retrograde
+    pub(crate) true_anomaly: f32,    // rad past periapsis in [0, 2π), or past the pivot's +X on a circle
+    pub(crate) circular: bool,       // a pivot circle: no periapsis to measure the anomaly from
+}
+
+impl OrbitalElements {
+    /// Osculating elements from the position `r` and velocity `v` relative to the primary,
+    /// with μ = G(M + m): the Keplerian orbit the body would follow if every other pull
+    /// stopped now.
+    pub(crate) fn from_state(r: Vec3, v: Vec3, mu: f32) -> Self {
+        let h = r.cross(v);
+        let e = v.cross(h) / mu - r.normalize_or_zero();
+        let energy = 0.5 * v.length_squared() - mu / r.length().max(1e-3);
+        let mut true_anomaly = if e.length() > 1e-5 { e.angle_between(r) } else { 0.0 };
+        if r.dot(v) < 0.0 {
+            true_anomaly = TAU - true_anomaly; // heading back in toward periapsis
+        }
+        Self {
+            semi_major_axis: -mu / (2.0 * energy),
+            eccentricity: e.length(),
+            inclination: if h == Vec3::ZERO { 0.0 } else { h.angle_between(Vec3::Y) },
+            true_anomaly: true_anomaly.rem_euclid(TAU),
+            circular: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn softened_head_on_collision_stays_bounded() {
+        const G: f32 = 1.0;
+        const MASS: f32 = 1.0;
+        const SOFTENING: f32 = 0.1;
+        const H: f32 = 1.0 / 120.0; // `nbody_gravity`'s largest substep
+        // Dead on course for each other, so without softening they'd meet at r = 0
+        let mut bodies = [(Vec3::new(-5.0, 0.0, 0.0), Vec3::X * 0.5, MASS), (Vec3::new(5.0, 0.0, 0.0), Vec3::NEG_X * 0.5, MASS)];
+        // The softened pull G·m·r / (r² + ε²)^{3/2} peaks at r = ε/√2
+        let max_acc = G * MASS * 2.0 / (3.0 * 3f32.sqrt() * SOFTENING * SOFTENING);
+        // Relative speed from energy conservation, with the well only ε deep: v² ≤ v₀² + 2G(m₁+m₂)/ε
+        let max_speed = (1.0f32 + 2.0 * G * 2.0 * MASS / SOFTENING).sqrt() / 2.0;
+        let mut closest = f32::MAX;
+        for step in 0..20 * 120 {
+            let masses = bodies.map(|(pos, _, mass)| (pos, mass));
+            for acc in gravity_accelerations(&masses, G, SOFTENING) {
+                assert!(acc.is_finite() && acc.length() <= max_acc * 1.001, "step {step}: acceleration {acc} (bound {max_acc})");
+            }
+            verlet_step(&mut bodies, G, SOFTENING, H);
+            for (pos, velocity, _) in bodies {
+                assert!(pos.is_finite() && pos.length() < 50.0, "step {step}: position {pos}");
+                assert!(velocity.is_finite() && velocity.length() < max_speed * 1.1, "step {step}: speed {} (bound {max_speed})", velocity.length());
+            }
+            closest = closest.min(bodies[0].0.distance(bodies[1].0));
+        }
+        assert!(closest < SOFTENING, "the bodies never met (closest {closest})");
+    }
+}
@@ -0,0 +1,451 @@
+//! Lighting, post-processing, render settings, and the night-lights material.
+
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::LoadState;
+use bevy::color::LinearRgba;
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::ecs::system::SystemParam;
+use bevy::pbr::wireframe::Wireframe;
+use bevy::pbr::{NotShadowCaster, PointLightShadowMap};
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{AsBindGroup, Extent3d, ShaderRef, TextureDimension, TextureFormat, TextureUsages};
+use bevy::render::renderer::{RenderAdapter, RenderDevice};
+use bevy::render::settings::WgpuFeatures;
+use bevy::render::view::ViewTarget;
+use bevy::window::{PrimaryWindow, WindowRef};
+use crate::{camera::*, params::*, scene::*};
+
+/// Earth's city-lights map (equirectangular, like `EARTH_TEXTURE`), relative to the
+/// `assets/` folder. Optional: `sync_night_lights` turns the lights on once it has loaded,
+/// and without it they stay off.
+const EARTH_NIGHT_TEXTURE: &str = "textures/earth_night.png";
+
+/// `night_lights.wgsl`, embedded by `SolarSystemUiPlugin` so the binary needs no shader files.
+pub(crate) const NIGHT_LIGHTS_SHADER: Handle<Shader> = Handle::weak_from_u128(0x6e19_47c2_85d3_4b0a_9f3e_21c8_d5a7_3b16);
+
+/// Material: the city-lights overlay. Drawn additively on a shell hugging Earth, so the
+/// planet's own PBR material is untouched underneath; the shader fades the lights in on
+/// the side facing away from `sun_position`.
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+pub(crate) struct NightLightsMaterial {
+    #[uniform(0)]
+    sun_position: Vec4, // world space (w unused)
+    #[uniform(0)]
+    strength: f32,
+    #[texture(1)]
+    #[sampler(2)]
+    texture: Handle<Image>,
+}
+
+impl Material for NightLightsMaterial {
+    fn fragment_shader() -> ShaderRef {
+        NIGHT_LIGHTS_SHADER.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Add
+    }
+}
+
+/// Component: the night-lights shell, a child of Earth managed by `sync_night_lights`.
+#[derive(Component)]
+struct NightLights;
+
+/// Component: camera-facing quad with vertex colors, parented to the main camera just
+/// inside its far plane and sized to fill the view by `apply_background`. It lives on
+/// `BACKDROP_LAYER`, which only the main camera renders, so the minimap doesn't see it.
+#[derive(Component)]
+pub(crate) struct GradientBackdrop;
+
+/// Render layer for camera-attached decorations that only the main camera draws.
+pub(crate) const BACKDROP_LAYER: usize = 1;
+
+/// Render layer of the `SceneBlit` sprite, which only the `SceneBlitCamera` draws.
+pub(crate) const BLIT_LAYER: usize = 2;
+
+/// Component: 2D camera that draws the `SceneBlit` sprite over the whole window while
+/// `render_scale` < 1; inactive otherwise.
+#[derive(Component)]
+pub(crate) struct SceneBlitCamera;
+
+/// Component: window-sized sprite showing the image the main camera renders into at
+/// `render_scale`, stretched back up to the window (see `apply_render_scale`).
+#[derive(Component)]
+pub(crate) struct SceneBlit;
+
+/// Query data for the night-lights shell.
+type NightShell = (Entity, &'static mut Handle<Mesh>, &'static Handle<NightLightsMaterial>);
+
+/// Whether `EARTH_NIGHT_TEXTURE` exists in the default `assets/` folder on disk.
+fn night_texture_present() -> bool {
+    FileAssetReader::get_base_path().join("assets").join(EARTH_NIGHT_TEXTURE).is_file()
+}
+
+/// Where `sync_night_lights` is with the optional `EARTH_NIGHT_TEXTURE`.
+#[derive(Default)]
+pub(crate) enum NightTexture {
+    #[default]
+    Unrequested,
+    Loading(Handle<Image>),
+    Loaded(Handle<Image>),
+    Missing { warned: bool }, // warned once the user asked for the lights anyway
+}
+
+/// Entities `sync_night_lights` works with: Earth (the shell's parent and mesh), the shell
+/// itself once spawned, and the Sun it lights from.
+#[derive(SystemParam)]
+pub(crate) struct NightLightsBodies<'w, 's> {
+    earth_q: Query<'w, 's, (Entity, &'static Handle<Mesh>), With<Earth>>,
+    shell_q: Query<'w, 's, NightShell, (With<NightLights>, Without<Earth>)>,
+    sun_q: Query<'w, 's, &'static GlobalTransform, With<Sun>>,
+}
+
+/// System: loads `EARTH_NIGHT_TEXTURE` and, once it has arrived, turns `night_lights` on
+/// (it defaults to off, so a checkout without the texture starts quietly). Then keeps the
+/// `NightLights` shell on Earth while `night_lights` is on: spawns it when missing (also
+/// after a reset) on Earth's own sphere, scaled up a touch so it sits just above the
+/// surface, and keeps its mesh (after a `mesh_detail` change), Sun position, and strength
+/// current. Despawns it when turned off. Switched on without the texture, it warns once and
+/// turns the lights back off.
+pub(crate) fn sync_night_lights(
+    mut commands: Commands,
+    bodies: NightLightsBodies,
+    mut materials: ResMut<Assets<NightLightsMaterial>>,
+    asset_server: Res<AssetServer>,
+    mut params: ResMut<SimulationParams>,
+    mut texture: Local<NightTexture>,
+) {
+    let NightLightsBodies { earth_q, mut shell_q, sun_q } = bodies;
+    match &mut *texture {
+        // Only asked for when it's there, so a checkout without it doesn't log a load error
+        NightTexture::Unrequested if night_texture_present() => *texture = NightTexture::Loading(asset_server.load(EARTH_NIGHT_TEXTURE)),
+        NightTexture::Unrequested => *texture = NightTexture::Missing { warned: false },
+        NightTexture::Loading(handle) => match asset_server.load_state(&*handle) {
+            LoadState::Loaded => {
+                info!("Found {EARTH_NIGHT_TEXTURE}, turning the city lights on");
+                *texture = NightTexture::Loaded(handle.clone());
+                params.night_lights = true;
+            }
+            LoadState::Failed(_) => *texture = NightTexture::Missing { warned: false },
+            _ => {} // still loading
+        },
+        NightTexture::Missing { warned } if params.night_lights => {
+            if !std::mem::replace(warned, true) {
+                warn!("Night lights unavailable: add {EARTH_NIGHT_TEXTURE} under assets/ to use them");
+            }
+            params.night_lights = false;
+        }
+        _ => {}
+    }
+    let shell = shell_q.get_single_mut();
+    let NightTexture::Loaded(lights_texture) = &*texture else { return };
+    if !params.night_lights {
+        if let Ok((entity, ..)) = shell {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+    let Ok((earth, earth_mesh)) = earth_q.get_single() else { return };
+    let sun_position = sun_q.get_single().map_or(Vec3::ZERO, |sun| sun.translation()).extend(0.0);
+    let Ok((_, mut mesh, material)) = shell else {
+        let shell = commands
+            .spawn((
+                MaterialMeshBundle {
+                    mesh: earth_mesh.clone(),
+                    material: materials.add(NightLightsMaterial {
+                        sun_position,
+                        strength: params.night_lights_strength,
+                        texture: lights_texture.clone(),
+                    }),
+                    transform: Transform::from_scale(Vec3::splat(1.003)),
+                    ..default()
+                },
+                NightLights,
+                NotShadowCaster,
+                Name::new("Night lights"),
+            ))
+            .id();
+        commands.entity(earth).add_child(shell);
+        return;
+    };
+    if *mesh != *earth_mesh {
+        *mesh = earth_mesh.clone();
+    }
+    // Only written on change, so the bind group isn't rebuilt every frame
+    let Some(current) = materials.get(material) else { return };
+    if current.sun_position != sun_position || current.strength != params.night_lights_strength {
+        if let Some(lights) = materials.get_mut(material) {
+            lights.sun_position = sun_position;
+            lights.strength = params.night_lights_strength;
+        }
+    }
+}
+
+/// System: writes the lighting sliders into `AmbientLight`, the Sun's point light (and
+/// glow, which follow `sun_radius`), and the directional fill light.
+pub(crate) fn apply_lighting(
+    mut ambient: ResMut<AmbientLight>,
+    mut sun_lights: Query<&mut PointLight>,
+    mut lights: Query<(&mut DirectionalLight, &mut Transform)>,
+    sun_q: Query<&Handle<StandardMaterial>, With<Sun>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    params: Res<SimulationParams>,
+) {
+    if !params.is_changed() {
+        return;
+    }
+    *ambient = params.ambient_light();
+    for mut light in &mut sun_lights {
+        light.intensity = params.sun_light_power();
+        light.radius = params.sun_radius;
+    }
+    // Only touch the material on a real change, so it isn't re-uploaded every frame
+    let emissive = params.sun_emissive();
+    for handle in &sun_q {
+        if materials.get(handle).is_some_and(|m| m.emissive != emissive) {
+            if let Some(material) = materials.get_mut(handle) {
+                material.emissive = emissive;
+            }
+        }
+    }
+    for (mut light, mut transform) in &mut lights {
+        light.illuminance = params.fill_illuminance;
+        transform.rotation = params.fill_light_rotation();
+    }
+}
+
+/// System: recolors the planets' materials for `color_scheme` when it changes, and when
+/// planets are (re)spawned with their natural colors after a reset.
+pub(crate) fn apply_color_scheme(
+    params: Res<SimulationParams>,
+    planets: Query<(Ref<Planet>, &Handle<StandardMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut applied: Local<Option<ColorScheme>>,
+) {
+    if *applied == Some(params.color_scheme) && !planets.iter().any(|(planet, _)| planet.is_added()) {
+        return;
+    }
+    *applied = Some(params.color_scheme);
+    for (planet, handle) in &planets {
+        let color = params.color_scheme.color(&planet);
+        if materials.get(handle).is_some_and(|mat| mat.base_color != color) {
+            if let Some(mat) = materials.get_mut(handle) {
+                mat.base_color = color;
+            }
+        }
+    }
+}
+
+/// System: writes the shadow toggle and map size into the Sun's light and
+/// `PointLightShadowMap` on a real change. "Off" waits for `WARM_UP_FRAMES`, so switching
+/// shadows back on never waits for their pipelines to compile.
+pub(crate) fn apply_shadows(
+    mut sun_lights: Query<&mut PointLight>,
+    mut shadow_map: ResMut<PointLightShadowMap>,
+    mut frames: Local<u32>,
+    params: Res<SimulationParams>,
+) {
+    const WARM_UP_FRAMES: u32 = 60; // about a second: pipelines compile in the background
+    *frames = frames.saturating_add(1);
+    let enabled = params.shadows || *frames < WARM_UP_FRAMES;
+    for mut light in &mut sun_lights {
+        if light.shadows_enabled != enabled {
+            light.shadows_enabled = enabled;
+        }
+    }
+    if shadow_map.size != params.shadow_map_size {
+        shadow_map.size = params.shadow_map_size;
+    }
+}
+
+/// System: `render_scale` below 1 points the main camera at an image that fraction of the
+/// window's physical size, which the `SceneBlit` sprite stretches back over the window;
+/// egui still draws straight to the window, so the UI stays sharp. The image follows
+/// window resizes, and at 1 the camera renders to the window again. Cursor and label
+/// positions convert between the two through `viewport_ratio`.
+pub(crate) fn apply_render_scale(
+    params: Res<SimulationParams>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut main_q: Query<&mut Camera, With<CameraController>>,
+    mut blit_q: Query<&mut Camera, (With<SceneBlitCamera>, Without<CameraController>)>,
+    mut sprite_q: Query<(&mut Sprite, &mut Handle<Image>), With<SceneBlit>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let (Ok(window), Ok(mut main), Ok(mut blit), Ok((mut sprite, mut texture))) =
+        (windows.get_single(), main_q.get_single_mut(), blit_q.get_single_mut(), sprite_q.get_single_mut())
+    else {
+        return;
+    };
+    let scaled = params.render_scale < 1.0;
+    if blit.is_active != scaled {
+        blit.is_active = scaled;
+    }
+    if !scaled {
+        if !matches!(main.target, RenderTarget::Window(WindowRef::Primary)) {
+            main.target = RenderTarget::default();
+        }
+        return;
+    }
+    let size = (window.physical_size().as_vec2() * params.render_scale).round().max(Vec2::ONE).as_uvec2();
+    let extent = Extent3d { width: size.x, height: size.y, depth_or_array_layers: 1 };
+    // Only borrowed mutably to resize: any `get_mut` re-uploads the image and re-targets the camera
+    let current = images
+        .get(texture.id())
+        .filter(|image| image.texture_descriptor.usage.contains(TextureUsages::RENDER_ATTACHMENT))
+        .map(|image| image.texture_descriptor.size);
+    match current {
+        Some(size) if size == extent => {}
+        Some(_) => {
+            if let Some(image) = images.get_mut(texture.id()) {
+                image.resize(extent);
+            }
+        }
+        // Still the sprite's default placeholder: make the render target
+        None => {
+            let mut image = Image::new_fill(
+                extent,
+                TextureDimension::D2,
+                &[0, 0, 0, 255],
+                TextureFormat::Bgra8UnormSrgb,
+                RenderAssetUsages::default(), // kept in the main world too, for the camera's target size
+            );
+            image.texture_descriptor.usage |= TextureUsages::RENDER_ATTACHMENT;
+            *texture = images.add(image);
+        }
+    }
+    if !matches!(&main.target, RenderTarget::Image(current) if *current == *texture) {
+        main.target = RenderTarget::Image(texture.clone());
+    }
+    if sprite.custom_size != Some(window.size()) {
+        sprite.custom_size = Some(window.size());
+    }
+}
+
+/// Ratio of the main camera's viewport to the window's logical size: below 1 while
+/// `apply_render_scale` has it rendering into a smaller image, 1 otherwise. Multiply a
+/// window position (cursor, egui) by it to get a viewport one, divide to go back.
+pub(crate) fn viewport_ratio(camera: &Camera, window: &Window) -> Vec2 {
+    match camera.logical_target_size() {
+        Some(size) if window.width() > 0.0 && window.height() > 0.0 => size / window.size(),
+        _ => Vec2::ONE,
+    }
+}
+
+/// System: when the MSAA choice changes (and once at startup), sets the `Msaa` resource,
+/// stepping down to the highest sample count the GPU supports for the HDR target. Bevy's
+/// own check only covers the window surface format, and an unsupported count on the HDR
+/// texture would fail pipeline creation. A fallback is written back so the UI shows it.
+pub(crate) fn apply_msaa(
+    mut msaa: ResMut<Msaa>,
+    mut params: ResMut<SimulationParams>,
+    adapter: Option<Res<RenderAdapter>>,
+    mut applied: Local<Option<MsaaLevel>>,
+) {
+    if *applied == Some(params.msaa) {
+        return;
+    }
+    let requested = params.msaa;
+    // No adapter (e.g. headless): nothing to check against
+    let flags = adapter.map(|adapter| adapter.get_texture_format_features(ViewTarget::TEXTURE_FORMAT_HDR).flags);
+    let supported = |level: MsaaLevel| match &flags {
+        Some(flags) => flags.sample_count_supported(level.msaa().samples()),
+        None => true,
+    };
+    let level = MsaaLevel::ALL
+        .into_iter()
+        .rev()
+        .filter(|&level| level.msaa().samples() <= requested.msaa().samples())
+        .find(|&level| supported(level))
+        .unwrap_or(MsaaLevel::Off);
+    if level == requested {
+        info!("MSAA: {}", level.label());
+    } else {
+        warn!("MSAA {} isn't supported on this GPU; using {}", requested.label(), level.label());
+        params.msaa = level;
+    }
+    *applied = Some(level);
+    *msaa = level.msaa();
+}
+
+/// Per-vertex colors for the backdrop quad: `top` at y = +0.5 blending to `bottom` at
+/// y = -0.5. Vertex colors are linear, so the sRGB picks are converted.
+pub(crate) fn gradient_colors(mesh: &Mesh, top: [f32; 3], bottom: [f32; 3]) -> Vec<[f32; 4]> {
+    let Some(positions) = mesh.attribute(Mesh::ATTRIBUTE_POSITION).and_then(|a| a.as_float3()) else { return Vec::new() };
+    let (top, bottom) = (LinearRgba::from(Color::srgb_from_array(top)), LinearRgba::from(Color::srgb_from_array(bottom)));
+    positions.iter().map(|p| bottom.mix(&top, p[1] + 0.5).to_f32_array()).collect()
+}
+
+/// System: background. Keeps `ClearColor` at the picked color and, with the gradient on,
+/// shows the backdrop quad, recoloring it when the picks change. Every frame it's re-fitted
+/// to the view (FOV, aspect, far plane) at 90% of the far distance, behind every body.
+pub(crate) fn apply_background(
+    mut clear: ResMut<ClearColor>,
+    mut backdrop_q: Query<(&mut Transform, &mut Visibility, &Handle<Mesh>), With<GradientBackdrop>>,
+    camera_q: Query<&Projection, With<CameraController>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    params: Res<SimulationParams>,
+) {
+    let Ok((mut transform, mut visibility, mesh)) = backdrop_q.get_single_mut() else { return };
+    if params.is_changed() {
+        *clear = params.clear_color();
+        *visibility = if params.gradient_background { Visibility::Inherited } else { Visibility::Hidden };
+        if let Some(mesh) = meshes.get_mut(mesh) {
+            let colors = gradient_colors(mesh, params.gradient_top, params.gradient_bottom);
+            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        }
+    }
+    let Ok(Projection::Perspective(perspective)) = camera_q.get_single() else { return };
+    let depth = perspective.far * 0.9;
+    let height = 2.0 * depth * (perspective.fov / 2.0).tan() * 1.05; // a little overscan at the edges
+    let fitted = Transform::from_xyz(0.0, 0.0, -depth).with_scale(Vec3::new(height * perspective.aspect_ratio, height, 1.0));
+    if *transform != fitted {
+        *transform = fitted;
+    }
+}
+
+/// System: copies `bloom_intensity` onto the camera's `BloomSettings` when it changes.
+pub(crate) fn apply_bloom(mut q: Query<&mut BloomSettings>, params: Res<SimulationParams>) {
+    if !params.is_changed() {
+        return;
+    }
+    for mut bloom in &mut q {
+        bloom.intensity = params.bloom_intensity;
+    }
+}
+
+/// Whether the GPU can draw wireframes: Bevy's wireframe pass needs
+/// `WgpuFeatures::POLYGON_MODE_LINE`, which WebGL2 and some mobile and integrated GPUs lack.
+/// The default `WgpuSettings` already enable every feature the adapter has, so it's on
+/// wherever it's available; nothing forces it, so startup never fails for want of it.
+pub(crate) fn line_mode_supported(device: Option<&RenderDevice>) -> bool {
+    device.is_some_and(|device| device.features().contains(WgpuFeatures::POLYGON_MODE_LINE))
+}
+
+/// Run condition: `line_mode_supported` for the app's render device.
+pub(crate) fn wireframes_supported(device: Option<Res<RenderDevice>>) -> bool {
+    line_mode_supported(device.as_deref())
+}
+
+/// System: gives each body a `Wireframe` while the global toggle or its `WireframeBody` is
+/// on. Bevy draws wireframes as an extra pass over the mesh, so the PBR shading still shows
+/// between the edges — handy for comparing the `mesh_detail` tessellations.
+pub(crate) fn apply_wireframes(
+    mut commands: Commands,
+    params: Res<SimulationParams>,
+    bodies: Query<(Entity, Has<WireframeBody>, Has<Wireframe>), With<BodyRadius>>,
+) {
+    for (entity, toggled, drawn) in &bodies {
+        match (params.wireframe || toggled, drawn) {
+            // `try_insert`: a reset may despawn the entity before the command applies
+            (true, false) => {
+                commands.entity(entity).try_insert(Wireframe);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<Wireframe>();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,996 @@
+//! The bodies: their components, spawning the scene, and keeping it in sync with its settings.
+
+use bevy::asset::LoadState;
+use bevy::audio::Volume;
+use bevy::color::{palettes::css, LinearRgba};
+use bevy::ecs::system::SystemParam;
+use bevy::math::primitives::Sphere;
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use std::f32::consts::{PI, TAU};
+use std::ops::RangeInclusive;
+use crate::{camera::*, eclipse::*, orbits::*, overlays::*, params::*, physics::*};
+
+/// Builds a body's UV sphere at the given detail level.
+fn sphere_mesh(radius: f32, detail: MeshDetail) -> Mesh {
+    let (sectors, stacks) = detail.subdivisions();
+    Sphere::new(radius).mesh().uv(sectors, stacks)
+}
+
+/// Component: the material color a body was spawned with, restored after tinting.
+#[derive(Component)]
+pub(crate) struct BaseColor(pub(crate) Color);
+
+/// Earth's albedo map, relative to the `assets/` folder. Optional: see `TextureFallback`.
+const EARTH_TEXTURE: &str = "textures/earth_albedo.png";
+
+/// Looping drone for the ambient hum, relative to the `assets/` folder. Optional: without
+/// it `update_ambient_hum` logs a warning and the scene stays silent.
+const AMBIENT_HUM: &str = "sounds/ambient_hum.ogg";
+
+/// Component: the looping `AMBIENT_HUM` player, retuned by `update_ambient_hum`.
+#[derive(Component)]
+pub(crate) struct AmbientHum;
+
+/// Component: solid color to use if the body's `base_color_texture` fails to load (e.g. the
+/// file isn't there). Removed by `texture_fallback` once the load has settled either way.
+#[derive(Component)]
+pub(crate) struct TextureFallback(Color);
+
+/// Tags for clarity / filtering.
+#[derive(Component)] pub(crate) struct Sun;
+#[derive(Component)] pub(crate) struct Earth;
+#[derive(Component)] pub(crate) struct Moon;
+#[derive(Component)] pub(crate) struct EarthPivot; // its rate defines the simulated year
+#[derive(Component)] struct NearSideMarker; // spot on the Moon's Earth-facing side (local -X)
+#[derive(Component)] pub(crate) struct MoonPivot;  // scaled by `moon_orbit_scale` instead of `planet_orbit_scale`
+#[derive(Component)] struct Satellite;  // body spawned by `spawn_moons`
+#[derive(Component)] pub(crate) struct SceneRoot;  // top-level entity from `spawn_bodies`, despawned on reset
+
+/// Event: rebuild the scene from defaults (sent by the "Reset simulation" button).
+#[derive(Event, Clone, Copy)]
+pub(crate) struct ResetSimulation {
+    pub(crate) keep_camera: bool, // leave the camera where it is instead of restoring the default view
+}
+
+/// Component: frame holding the Moon's orbit plane, parented to Earth between Earth and
+/// the Moon's pivot. `orient_moon_plane` cancels Earth's world rotation (spin, tilt, orbit
+/// yaw) and applies `SimulationParams.moon_inclination` about world X, so the orbit is
+/// inclined to the ecliptic and inertially fixed rather than dragged around by Earth's spin.
+#[derive(Component)]
+pub(crate) struct MoonOrbitPlane;
+
+/// Component: like `MoonOrbitPlane` without the tilt: holds one body's extra moons from
+/// `spawn_moons`, kept level and unscaled by `orient_moon_plane` so the parent's spin and
+/// axial tilt don't drag the moons' orbits around.
+#[derive(Component)]
+pub(crate) struct SatelliteFrame;
+
+/// Component: keeps a body's spin axis fixed in world space while its parent pivot
+/// rotates. Real axial tilt is inertially fixed (that's what causes seasons), so
+/// `apply_rotations` undoes the pivot's yaw on the body's local rotation.
+#[derive(Component)]
+pub(crate) struct InertialAxis;
+
+/// Component: a pickable celestial body and its sphere radius (before `Transform.scale`).
+#[derive(Component)]
+pub(crate) struct BodyRadius(pub(crate) f32);
+
+/// Component: the selection panel's per-body wireframe toggle, honored by `apply_wireframes`
+/// even while the global toggle is off.
+#[derive(Component)]
+pub(crate) struct WireframeBody;
+
+/// Component: text drawn above the body by `draw_labels`.
+#[derive(Component)]
+pub(crate) struct BodyLabel(pub(crate) String);
+
+/// Component: real mean radius in Earth radii, used by `apply_body_scales` in realistic mode.
+#[derive(Component)]
+pub(crate) struct TrueRadius(f32);
+
+/// Component: extra size factor from Ctrl+scroll on the selection (`resize_selection`),
+/// multiplied into the scale `apply_body_scales` would otherwise give the body.
+#[derive(Component)]
+pub(crate) struct SizeScale(pub(crate) f32);
+
+impl SizeScale {
+    pub(crate) const RANGE: RangeInclusive<f32> = 0.2..=5.0;
+    pub(crate) const STEP: f32 = 1.1; // factor per wheel line
+}
+
+/// Component + spawn spec: data-driven description of a planet besides Earth.
+/// `setup` spawns a pivot + body per entry in `PLANETS`; the body keeps a copy.
+#[derive(Component, Clone, Copy)]
+pub(crate) struct Planet {
+    pub(crate) name: &'static str,
+    pub(crate) orbit_au: f32,       // real mean Sun distance (AU), mapped through `scene_orbit_radius`
+    body_radius: f32,    // sphere radius in scene units (artistic, not to scale)
+    pub(crate) color: Color,
+    pub(crate) orbit_period_days: f32, // real orbital (sidereal) period → pivot `Orbit` speed
+    rotation_period_days: f32, // real sidereal rotation period → body `Spin` speed
+    axial_tilt_deg: f32, // tilt > 90° already makes the spin retrograde (Venus, Uranus)
+    true_radius: f32,    // real mean radius in Earth radii
+    roughness: f32,      // `perceptual_roughness`: rocky worlds near 1, cloud tops glossier
+    metallic: f32,       // `metallic`: ~0 for everything but iron-rich Mercury's hint
+    pub(crate) eccentricity: f32,   // real orbital eccentricity, used when the planet rides its ellipse
+    rings: Option<Rings>, // ring system, spawned as a child of the body
+}
+
+/// Component: flat ring system in its body's equatorial plane (the body's local XZ),
+/// so it inherits axial tilt and orbit through the hierarchy.
+#[derive(Component, Clone, Copy)]
+struct Rings {
+    inner: f32, // inner radius, in body radii
+    outer: f32, // outer radius, in body radii
+    color: Color,
+}
+
+/// Builds a flat annulus in the XZ plane, facing +Y, with `segments` quads around.
+/// UVs run radially (u: 0 inner → 1 outer) and around (v: 0 → 1).
+fn annulus_mesh(inner: f32, outer: f32, segments: u32) -> Mesh {
+    let mut positions = Vec::with_capacity(2 * (segments as usize + 1));
+    let mut uvs = Vec::with_capacity(positions.capacity());
+    let mut indices = Vec::with_capacity(6 * segments as usize);
+    for i in 0..=segments {
+        let v = i as f32 / segments as f32;
+        let (sin, cos) = (v * TAU).sin_cos();
+        positions.push(Vec3::new(inner * cos, 0.0, inner * sin));
+        positions.push(Vec3::new(outer * cos, 0.0, outer * sin));
+        uvs.push(Vec2::new(0.0, v));
+        uvs.push(Vec2::new(1.0, v));
+        if i < segments {
+            let base = 2 * i;
+            indices.extend([base, base + 2, base + 1, base + 1, base + 2, base + 3]);
+        }
+    }
+    let normals = vec![Vec3::Y; positions.len()];
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::RENDER_WORLD)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+/// The planets spawned alongside Earth, innermost first.
+pub(crate) const PLANETS: [Planet; 7] = [
+    Planet { name: "Mercury", orbit_au: 0.387, body_radius: 0.19, color: Color::srgb(0.6, 0.55, 0.5), orbit_period_days: 87.97, rotation_period_days: 58.65, axial_tilt_deg: 0.03, true_radius: 0.383, roughness: 0.9, metallic: 0.1, eccentricity: 0.206, rings: None },
+    Planet { name: "Venus", orbit_au: 0.723, body_radius: 0.47, color: Color::srgb(0.9, 0.8, 0.55), orbit_period_days: 224.70, rotation_period_days: 243.02, axial_tilt_deg: 177.4, true_radius: 0.949, roughness: 0.6, metallic: 0.0, eccentricity: 0.007, rings: None },
+    Planet { name: "Mars", orbit_au: 1.524, body_radius: 0.27, color: Color::srgb(0.8, 0.35, 0.2), orbit_period_days: 686.98, rotation_period_days: 1.026, axial_tilt_deg: 25.19, true_radius: 0.532, roughness: 0.95, metallic: 0.0, eccentricity: 0.093, rings: None },
+    Planet { name: "Jupiter", orbit_au: 5.203, body_radius: 0.9, color: Color::srgb(0.8, 0.65, 0.5), orbit_period_days: 4332.6, rotation_period_days: 0.4135, axial_tilt_deg: 3.13, true_radius: 11.21, roughness: 0.45, metallic: 0.0, eccentricity: 0.049, rings: None },
+    Planet { name: "Saturn", orbit_au: 9.537, body_radius: 0.8, color: Color::srgb(0.9, 0.8, 0.6), orbit_period_days: 10759.0, rotation_period_days: 0.4440, axial_tilt_deg: 26.73, true_radius: 9.45, roughness: 0.45, metallic: 0.0, eccentricity: 0.057, rings: Some(Rings { inner: 1.25, outer: 2.3, color: Color::srgba(0.85, 0.75, 0.55, 0.6) }) },
+    Planet { name: "Uranus", orbit_au: 19.19, body_radius: 0.6, color: Color::srgb(0.6, 0.85, 0.9), orbit_period_days: 30685.0, rotation_period_days: 0.7183, axial_tilt_deg: 97.77, true_radius: 4.01, roughness: 0.35, metallic: 0.0, eccentricity: 0.046, rings: None },
+    Planet { name: "Neptune", orbit_au: 30.07, body_radius: 0.58, color: Color::srgb(0.3, 0.45, 0.9), orbit_period_days: 60190.0, rotation_period_days: 0.6713, axial_tilt_deg: 28.32, true_radius: 3.88, roughness: 0.35, metallic: 0.0, eccentricity: 0.011, rings: None },
+];
+
+/// Maps a real distance in AU to scene units logarithmically, so Neptune (30 AU)
+/// stays on screen. 1 AU lands on Earth's default orbit radius (3.0).
+pub(crate) fn scene_orbit_radius(au: f32) -> f32 {
+    3.0 * (1.0 + au).log2()
+}
+
+/// Inverse of `scene_orbit_radius`: the real distance in AU a scene distance from the Sun stands for.
+pub(crate) fn scene_to_au(distance: f32) -> f32 {
+    (distance / 3.0).exp2() - 1.0
+}
+
+/// Seed for the asteroid belt, so every reset lays it out the same way.
+const ASTEROID_SEED: u64 = 0xA57E_401D;
+
+/// Tag: the comet body. It always follows its `EllipticalOrbit` with Kepler motion, since
+/// uniform parametric timing would miss the fast swing through perihelion.
+#[derive(Component)]
+pub(crate) struct Comet;
+
+/// Tag: the comet's tilted orbital-plane frame (its parent, at the root).
+#[derive(Component)]
+pub(crate) struct CometOrbitPlane;
+
+/// Tag: the `Orbit` pivot carrying one rock of the asteroid belt.
+#[derive(Component)]
+pub(crate) struct Asteroid;
+
+/// Resource: procedural starfield tunables, read once by `setup_skybox`.
+#[derive(Resource)]
+pub(crate) struct StarfieldConfig {
+    pub(crate) star_count: usize,
+    pub(crate) seed: u64,   // same seed → same sky, so screenshots are reproducible
+    pub(crate) radius: f32, // sky sphere radius; must stay inside the camera's far plane
+}
+
+/// Component: the starfield entity, re-centered on the camera every frame.
+#[derive(Component)]
+pub(crate) struct Skybox;
+
+/// Tiny deterministic PRNG (SplitMix64), so procedural content is reproducible
+/// without pulling in an RNG crate.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniform in [lo, hi).
+    fn range(&mut self, lo: f32, hi: f32) -> f32 {
+        lo + (hi - lo) * self.next_f32()
+    }
+}
+
+/// Startup system: spawns the bodies (see `spawn_bodies`).
+pub(crate) fn setup_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    params: Res<SimulationParams>,
+) {
+    spawn_bodies(&mut commands, &mut meshes, &mut materials, &asset_server, &params);
+}
+
+/// Spawns Sun, Earth (with tilt), Moon, the other planets, and their pivots, using
+/// parent-child hierarchy to express spatial relationships. Every root entity gets
+/// `SceneRoot`, so `reset_simulation` can despawn the lot and call this again.
+fn spawn_bodies(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    asset_server: &AssetServer,
+    params: &SimulationParams,
+) {
+    // Sun: emissive PBR sphere + a gentle spin (purely visual)
+    let sun_mesh = meshes.add(sphere_mesh(1.0, params.mesh_detail));
+    let sun_mat = materials.add(StandardMaterial {
+        base_color: css::ORANGE.into(),
+        // In Bevy 0.14, emissive is LinearRgba. Scaled well past 1.0 so the HDR camera's
+        // bloom picks it up; lit planets stay far dimmer, so they shade normally.
+        emissive: params.sun_emissive(),
+        ..default()
+    });
+    commands.spawn((
+        PbrBundle {
+            mesh: sun_mesh,
+            material: sun_mat,
+            transform: Transform::from_translation(Vec3::ZERO).with_scale(Vec3::splat(params.sun_radius)),
+            ..default()
+        },
+        Sun,
+        SceneRoot,
+        Name::new("Sun"),
+        BodyLabel("Sun".into()),
+        BodyRadius(1.0),
+        TrueRadius(109.1),
+        Mass(params.sun_mass),
+        TrackedMotion::default(),
+        Spin::new(rate_for_period(SUN_ROTATION_DAYS)),
+        // Stays put in the world frame; traces the apparent solar orbit in Earth's
+        OrbitTrail::new(Color::srgb(1.0, 0.85, 0.3)),
+        // The light sits inside the Sun's sphere, which mustn't shadow the whole scene
+        NotShadowCaster,
+    ))
+    .with_children(|sun| {
+        // Sunlight radiates from the Sun itself, so every body's terminator faces it
+        sun.spawn(PointLightBundle {
+            point_light: PointLight {
+                intensity: params.sun_light_power(),
+                range: 200.0, // past Neptune, even in physics mode drift
+                radius: params.sun_radius, // the Sun's own radius, for softer shadows
+                shadows_enabled: true,
+                ..default()
+            },
+            // Not inherited, so hiding the Sun's sphere leaves the scene lit
+            visibility: Visibility::Visible,
+            ..default()
+        });
+    });
+
+    // Earth: tilted axis (~23.44°), initially placed along +X at orbit radius.
+    // The texture is tinted white until it either loads or falls back to the solid color.
+    let earth_mesh = meshes.add(sphere_mesh(0.5, params.mesh_detail));
+    let earth_color = Color::srgb(0.2, 0.4, 1.0);
+    let earth_mat = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        base_color_texture: Some(asset_server.load(EARTH_TEXTURE)),
+        perceptual_roughness: 0.6, // a hint of ocean sheen
+        ..default()
+    });
+    let tilt = EARTH_AXIAL_TILT_DEG.to_radians();
+    // Start phases: circular orbits start with their pivot yawed, counter-yawing
+    // `InertialAxis` tilts; an elliptical Earth starts along its ellipse instead
+    let earth_phase = params.start_phase(PhaseTarget::Earth);
+    let earth_yaw = if params.use_elliptical_orbit { 0.0 } else { earth_phase };
+    let mut earth_ellipse = EllipticalOrbit::new(params.earth_orbit_radius, params.earth_eccentricity, rate_for_period(EARTH_YEAR_DAYS));
+    earth_ellipse.active = params.use_elliptical_orbit;
+    if earth_ellipse.active {
+        earth_ellipse.set_phase(earth_phase);
+    }
+    let earth = commands
+        .spawn((
+            PbrBundle {
+                mesh: earth_mesh,
+                material: earth_mat,
+                transform: Transform::from_xyz(params.earth_orbit_radius, 0.0, 0.0)
+                    .with_rotation(Quat::from_rotation_y(-earth_yaw) * Quat::from_axis_angle(Vec3::Z, tilt)),
+                ..default()
+            },
+            Earth,
+            Name::new("Earth"),
+            BodyLabel("Earth".into()),
+            BodyRadius(0.5),
+            TrueRadius(1.0),
+            Mass(params.earth_mass),
+            TrackedMotion::default(),
+            BaseColor(Color::WHITE),
+            TextureFallback(earth_color),
+            InertialAxis,
+            Spin::new(rate_for_period(EARTH_DAY_DAYS)),
+            OrbitTrail::new(Color::srgb(0.3, 0.6, 1.0)),
+            // Used when `use_elliptical_orbit` is on; same mean rate as the circular pivot
+            earth_ellipse,
+        ))
+        .id();
+
+    // Earth pivot: rotates to carry the Earth around the Sun in a circle.
+    // Kept at the root (like the other planets) so the Sun's spin doesn't add to the orbit.
+    commands.spawn((
+        SpatialBundle::from_transform(Transform::from_rotation(Quat::from_rotation_y(earth_yaw))),
+        Orbit::new(rate_for_period(EARTH_YEAR_DAYS)),
+        EarthPivot,
+        SceneRoot,
+    ))
+    .push_children(&[earth]);
+
+    // Moon orbit plane: child of Earth (so it follows Earth around the Sun), tilted by the
+    // inclination; the pivot inside it rotates about the plane's local Y
+    let moon_plane = commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_rotation(Quat::from_rotation_x(
+                params.moon_inclination,
+            ))),
+            MoonOrbitPlane,
+        ))
+        .id();
+    // Like Earth's: an elliptical Moon starts along its ellipse, with the pivot unyawed
+    let moon_phase = params.start_phase(PhaseTarget::Moon);
+    let mut moon_ellipse = EllipticalOrbit::new(params.moon_orbit_radius, params.moon_eccentricity, rate_for_period(MOON_MONTH_DAYS));
+    moon_ellipse.active = params.moon_elliptical;
+    if moon_ellipse.active {
+        moon_ellipse.set_phase(moon_phase);
+    }
+
+    // Moon: smaller sphere offset along +X in Earth's local space, built like the extra
+    // moons and then given what only the real Moon has
+    let moon_color = Color::srgb(0.8, 0.8, 0.8);
+    let moon_mat = materials.add(StandardMaterial {
+        base_color: moon_color,
+        perceptual_roughness: 0.98, // dusty regolith: no highlight at all
+        ..default()
+    });
+    let moon_spec = MoonSpec {
+        name: "Moon".into(),
+        orbit_radius: params.moon_orbit_radius,
+        body_radius: 0.18,
+        angular_speed: rate_for_period(MOON_MONTH_DAYS),
+    };
+    let moon_yaw = if params.moon_elliptical { 0.0 } else { moon_phase };
+    let (moon_pivot, moon) = spawn_moon(commands, meshes, moon_mat, &moon_spec, moon_yaw, params.mesh_detail);
+    commands
+        .entity(moon)
+        .insert((
+            Moon,
+            TrueRadius(0.273),
+            Mass(params.moon_mass),
+            BaseColor(moon_color),
+            Spin::new(rate_for_period(MOON_MONTH_DAYS)), // synchronous, like the real Moon
+            OrbitTrail::new(Color::srgb(0.7, 0.7, 0.7)),
+            // Used when `moon_elliptical` is on, in the orbit plane (which carries the inclination)
+            moon_ellipse,
+        ))
+        .with_children(|moon| {
+            // Near-side spot, so tidal locking is easy to see
+            moon.spawn((
+                PbrBundle {
+                    mesh: meshes.add(sphere_mesh(0.05, MeshDetail::Low)),
+                    material: materials.add(StandardMaterial {
+                        base_color: Color::srgb(0.6, 0.1, 0.1),
+                        ..default()
+                    }),
+                    transform: Transform::from_xyz(-0.16, 0.0, 0.0),
+                    ..default()
+                },
+                NearSideMarker,
+                NotShadowCaster,
+            ));
+        });
+
+    // Remaining planets from the `PLANETS` table. Their pivots live at the root rather than
+    // under the Sun, so the Sun's cosmetic spin doesn't leak into their orbital rates.
+    // Like Earth, a planet on its ellipse starts along it with the pivot unyawed.
+    for (index, planet) in PLANETS.into_iter().enumerate() {
+        let phase = params.start_phase(PhaseTarget::Planet(index));
+        let (radius, rate) = (params.planet_orbit_radii[index], params.planet_rate(index));
+        let mut ellipse = EllipticalOrbit::new(radius, planet.eccentricity, rate);
+        ellipse.active = params.elliptical_planets[index];
+        let (yaw, translation) = if ellipse.active {
+            ellipse.set_phase(phase);
+            (0.0, ellipse.position(params.use_kepler_motion))
+        } else {
+            (phase, Vec3::X * radius)
+        };
+        let body = commands
+            .spawn((
+                PbrBundle {
+                    mesh: meshes.add(sphere_mesh(planet.body_radius, params.mesh_detail)),
+                    material: materials.add(StandardMaterial {
+                        base_color: planet.color,
+                        perceptual_roughness: planet.roughness,
+                        metallic: planet.metallic,
+                        ..default()
+                    }),
+                    transform: Transform::from_translation(translation).with_rotation(
+                        Quat::from_rotation_y(-yaw) * Quat::from_axis_angle(Vec3::Z, planet.axial_tilt_deg.to_radians()),
+                    ),
+                    ..default()
+                },
+                Name::new(planet.name),
+                BodyLabel(planet.name.into()),
+                BodyRadius(planet.body_radius),
+                TrueRadius(planet.true_radius),
+                TrackedMotion::default(),
+                InertialAxis,
+                Spin::new(rate_for_period(planet.rotation_period_days.into())),
+                ellipse,
+                planet,
+            ))
+            .id();
+        if let Some(rings) = planet.rings {
+            let ring = commands
+                .spawn((
+                    PbrBundle {
+                        mesh: meshes.add(annulus_mesh(
+                            rings.inner * planet.body_radius,
+                            rings.outer * planet.body_radius,
+                            128,
+                        )),
+                        material: materials.add(StandardMaterial {
+                            base_color: rings.color,
+                            alpha_mode: AlphaMode::Blend,
+                            cull_mode: None, // visible from above and below
+                            ..default()
+                        }),
+                        ..default()
+                    },
+                    rings,
+                ))
+                .id();
+            commands.entity(body).push_children(&[ring]);
+        }
+        commands
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_rotation(Quat::from_rotation_y(yaw))),
+                Orbit::new(rate),
+                SceneRoot,
+            ))
+            .push_children(&[body]);
+    }
+
+    spawn_asteroid_belt(commands, meshes, materials, params);
+
+    // Build hierarchy:
+    // earth_pivot (rotates: carries Earth around the Sun at the origin)
+    //  └─ Earth
+    //      └─ moon_plane (inclined, inertially fixed)
+    //          └─ moon_pivot (rotates: carries Moon around Earth)
+    //              └─ Moon
+    commands.entity(moon_plane).push_children(&[moon_pivot]);
+    commands.entity(earth).push_children(&[moon_plane]);
+}
+
+/// Spawns the asteroid belt: one pivot + rock per asteroid, all sharing a single mesh and
+/// material so the renderer batches them into a few draws. Each pivot starts at a random
+/// phase and a small random inclination, and `rotate_local_y` turns it about that tilted
+/// axis. Speeds follow Kepler's third law from Earth's rate (ω ∝ a^-3/2).
+fn spawn_asteroid_belt(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    params: &SimulationParams,
+) {
+    let rock_mesh = meshes.add(sphere_mesh(1.0, MeshDetail::Low));
+    let rock_mat = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.45, 0.42, 0.38),
+        perceptual_roughness: 1.0,
+        ..default()
+    });
+    let mut rng = SplitMix64(ASTEROID_SEED);
+    let (inner_au, outer_au) = (params.belt_inner_au.min(params.belt_outer_au), params.belt_inner_au.max(params.belt_outer_au));
+    for _ in 0..params.belt_count {
+        let au = rng.range(inner_au, outer_au);
+        let phase = rng.range(0.0, TAU);
+        let inclination = rng.range(-0.15, 0.15);
+        let rock = commands
+            .spawn((
+                PbrBundle {
+                    mesh: rock_mesh.clone(),
+                    material: rock_mat.clone(),
+                    transform: Transform::from_xyz(scene_orbit_radius(au), 0.0, 0.0)
+                        .with_scale(Vec3::splat(rng.range(0.02, 0.06))),
+                    ..default()
+                },
+                NotShadowCaster,
+            ))
+            .id();
+        commands
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_rotation(
+                    Quat::from_rotation_x(inclination) * Quat::from_rotation_y(phase),
+                )),
+                Orbit::new(rate_for_period(EARTH_YEAR_DAYS * f64::from(au).powf(1.5))),
+                Asteroid,
+                SceneRoot,
+            ))
+            .push_children(&[rock]);
+    }
+}
+
+/// One moon for `spawn_moons`.
+struct MoonSpec {
+    name: String,
+    orbit_radius: f32,  // distance from the parent's center
+    body_radius: f32,   // sphere radius
+    angular_speed: f32, // pivot `Orbit` speed (rad per sim s), scaled like the Moon's
+}
+
+impl MoonSpec {
+    /// `count` moons for a parent of sphere radius `parent_radius`: evenly spaced from
+    /// `first_orbit` outward, with speeds falling off by Kepler's third law from
+    /// `first_speed`. Named from `names`, then by number.
+    fn series(parent: &str, names: &[&str], count: usize, first_orbit: f32, first_speed: f32, parent_radius: f32) -> Vec<Self> {
+        (0..count)
+            .map(|i| {
+                let orbit_radius = first_orbit + 0.45 * parent_radius * i as f32;
+                Self {
+                    name: names.get(i).map_or_else(|| format!("{parent} moon {}", i + 1), |name| name.to_string()),
+                    orbit_radius,
+                    body_radius: 0.07 + 0.02 * (i % 3) as f32,
+                    angular_speed: first_speed * (orbit_radius / first_orbit).powf(-1.5),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Builds one moon from `moon`: a sphere at its orbit radius along +X, under a `MoonPivot`
+/// `Orbit` yawed to `phase`, so `animate_orbits` scales it like the Moon. Returns the
+/// (pivot, body) entities for the caller to parent and to add what else the moon needs.
+fn spawn_moon(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    material: Handle<StandardMaterial>,
+    moon: &MoonSpec,
+    phase: f32,
+    detail: MeshDetail,
+) -> (Entity, Entity) {
+    let body = commands
+        .spawn((
+            PbrBundle {
+                mesh: meshes.add(sphere_mesh(moon.body_radius, detail)),
+                material,
+                transform: Transform::from_xyz(moon.orbit_radius, 0.0, 0.0),
+                ..default()
+            },
+            Name::new(moon.name.clone()),
+            BodyLabel(moon.name.clone()),
+            BodyRadius(moon.body_radius),
+            TrackedMotion::default(),
+        ))
+        .id();
+    let pivot = commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_rotation(Quat::from_rotation_y(phase))),
+            Orbit::new(moon.angular_speed),
+            MoonPivot,
+        ))
+        .push_children(&[body])
+        .id();
+    (pivot, body)
+}
+
+/// Builds `moons` around `parent` with `spawn_moon`: a `SatelliteFrame` child holding their
+/// pivots, each starting at an evenly spread phase so they don't all line up along +X.
+fn spawn_moons(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    parent: Entity,
+    moons: &[MoonSpec],
+    detail: MeshDetail,
+) {
+    if moons.is_empty() {
+        return;
+    }
+    let material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.75, 0.7, 0.62),
+        ..default()
+    });
+    let frame = commands.spawn((SpatialBundle::default(), SatelliteFrame)).id();
+    for (i, moon) in moons.iter().enumerate() {
+        let phase = TAU * i as f32 / moons.len() as f32;
+        let (pivot, body) = spawn_moon(commands, meshes, material.clone(), moon, phase, detail);
+        commands.entity(body).insert(Satellite);
+        commands.entity(frame).push_children(&[pivot]);
+    }
+    commands.entity(parent).push_children(&[frame]);
+}
+
+/// System: keeps the number of extra moons around Earth and Jupiter at the sliders,
+/// rebuilding a parent's whole set when its count changes (a count of 0 just removes
+/// them). Runs every frame rather than on change, so it also repopulates after a reset.
+pub(crate) fn sync_moons(
+    mut commands: Commands,
+    frames: Query<(Entity, &Parent, &Children), With<SatelliteFrame>>,
+    earth_q: Query<(Entity, &BodyRadius), With<Earth>>,
+    planets: Query<(Entity, &Planet, &BodyRadius)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    params: Res<SimulationParams>,
+) {
+    const GALILEAN: [&str; 4] = ["Io", "Europa", "Ganymede", "Callisto"];
+    let earth = earth_q.get_single().ok().map(|(entity, radius)| {
+        // Outside the real Moon, with its rate as the reference for Kepler's law
+        let first = params.moon_orbit_radius + 0.4;
+        let speed = rate_for_period(MOON_MONTH_DAYS) * (first / params.moon_orbit_radius).powf(-1.5);
+        (entity, MoonSpec::series("Earth", &[], params.extra_earth_moons, first, speed, radius.0))
+    });
+    let jupiter = planets.iter().find(|(_, planet, _)| planet.name == "Jupiter").map(|(entity, _, radius)| {
+        // Io's 1.77-day period for the innermost
+        (entity, MoonSpec::series("Jupiter", &GALILEAN, params.jupiter_moons, 1.6 * radius.0, rate_for_period(1.769), radius.0))
+    });
+    for (parent, moons) in [earth, jupiter].into_iter().flatten() {
+        let existing = frames.iter().find(|(_, frame_parent, _)| frame_parent.get() == parent);
+        if existing.map_or(0, |(_, _, pivots)| pivots.len()) == moons.len() {
+            continue;
+        }
+        if let Some((frame, _, _)) = existing {
+            commands.entity(frame).despawn_recursive();
+        }
+        spawn_moons(&mut commands, &mut meshes, &mut materials, parent, &moons, params.mesh_detail);
+    }
+}
+
+/// System: spawns the comet when `show_comet` is on and it's missing (also after a reset),
+/// and despawns it when turned off. Perihelion at 1.35 (just outside the Sun), aphelion
+/// past Saturn; mean motion follows Kepler's third law from Earth's rate at radius 3.
+pub(crate) fn sync_comet(
+    mut commands: Commands,
+    planes: Query<Entity, With<CometOrbitPlane>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    params: Res<SimulationParams>,
+) {
+    match (params.show_comet, planes.get_single()) {
+        (true, Err(_)) => {
+            let (a, e) = (9.0, 0.85);
+            let comet = commands
+                .spawn((
+                    PbrBundle {
+                        mesh: meshes.add(sphere_mesh(0.1, params.mesh_detail)),
+                        material: materials.add(StandardMaterial {
+                            base_color: Color::srgb(0.85, 0.9, 1.0),
+                            emissive: LinearRgba::rgb(0.3, 0.4, 0.6),
+                            ..default()
+                        }),
+                        // Start at perihelion, where `kepler_position` puts M = 0
+                        transform: Transform::from_xyz(a * (1.0 - e), 0.0, 0.0),
+                        ..default()
+                    },
+                    Comet,
+                    Name::new("Comet"),
+                    BodyLabel("Comet".into()),
+                    BodyRadius(0.1),
+                    TrackedMotion::default(),
+                    OrbitTrail::new(Color::srgb(0.6, 0.8, 1.0)),
+                    EllipticalOrbit { active: true, ..EllipticalOrbit::new(a, e, rate_for_period(EARTH_YEAR_DAYS * f64::from(a / 3.0).powf(1.5))) },
+                ))
+                .id();
+            // Inclined 15° to the ecliptic, with perihelion rotated off every planet's start
+            commands
+                .spawn((
+                    SpatialBundle::from_transform(Transform::from_rotation(
+                        Quat::from_rotation_y(2.2) * Quat::from_rotation_x(15_f32.to_radians()),
+                    )),
+                    CometOrbitPlane,
+                    SceneRoot,
+                ))
+                .push_children(&[comet]);
+        }
+        (false, Ok(plane)) => commands.entity(plane).despawn_recursive(),
+        _ => {}
+    }
+}
+
+/// System: respawns the asteroid belt when its count or band changes.
+pub(crate) fn rebuild_asteroid_belt(
+    mut commands: Commands,
+    belt: Query<Entity, With<Asteroid>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    params: Res<SimulationParams>,
+    mut built: Local<Option<(usize, f32, f32)>>,
+) {
+    let wanted = (params.belt_count, params.belt_inner_au, params.belt_outer_au);
+    // Startup (and reset) already spawn the belt from the current params
+    if *built.get_or_insert(wanted) == wanted {
+        return;
+    }
+    *built = Some(wanted);
+    for pivot in &belt {
+        commands.entity(pivot).despawn_recursive();
+    }
+    spawn_asteroid_belt(&mut commands, &mut meshes, &mut materials, &params);
+}
+
+/// Startup system: starts the `AMBIENT_HUM` loop, silent until `update_ambient_hum` sets
+/// its volume from the sliders.
+pub(crate) fn setup_ambient_hum(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Name::new("Ambient hum"),
+        AmbientHum,
+        AudioBundle {
+            source: asset_server.load(AMBIENT_HUM),
+            settings: PlaybackSettings::LOOP.with_volume(Volume::new(0.0)),
+        },
+    ));
+}
+
+/// Query data for the ambient hum player; the sink is absent until the sound has loaded.
+type HumPlayer = (Entity, &'static Handle<AudioSource>, Option<&'static AudioSink>);
+
+/// System: ties the hum to the orbit speed. Playback speed (so pitch) rises with
+/// √|`orbit_speed_scale`| — an octave down while paused, ~1.6× at the 5× maximum — and the
+/// volume fades toward a third of `hum_volume` as the orbits slow. If the sound can't be
+/// loaded, warns once and despawns the player.
+pub(crate) fn update_ambient_hum(
+    mut commands: Commands,
+    hum_q: Query<HumPlayer, With<AmbientHum>>,
+    asset_server: Res<AssetServer>,
+    params: Res<SimulationParams>,
+) {
+    let Ok((entity, source, sink)) = hum_q.get_single() else { return };
+    let Some(sink) = sink else {
+        // Bevy adds the sink once the source has loaded
+        if let LoadState::Failed(err) = asset_server.load_state(source) {
+            warn!("Ambient hum unavailable ({AMBIENT_HUM}), continuing without sound: {err}");
+            commands.entity(entity).despawn();
+        }
+        return;
+    };
+    let speed = if params.paused { 0.0 } else { params.orbit_speed_scale.abs() };
+    let volume = if params.hum_muted { 0.0 } else { params.hum_volume * (1.0 + 2.0 * speed.min(1.0)) / 3.0 };
+    let pitch = (0.5 + 0.5 * speed.sqrt()).min(2.0);
+    if sink.volume() != volume {
+        sink.set_volume(volume);
+    }
+    if sink.speed() != pitch {
+        sink.set_speed(pitch);
+    }
+}
+
+/// Startup system: builds the starfield as one mesh of tiny camera-facing quads scattered
+/// uniformly over a large sphere. Unlit + no shadows, so the Sun's light never touches it.
+pub(crate) fn setup_skybox(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    config: Res<StarfieldConfig>,
+) {
+    let mut rng = SplitMix64(config.seed);
+    let mut positions = Vec::with_capacity(config.star_count * 4);
+    let mut normals = Vec::with_capacity(config.star_count * 4);
+    let mut colors = Vec::with_capacity(config.star_count * 4);
+    let mut indices = Vec::with_capacity(config.star_count * 6);
+    for i in 0..config.star_count as u32 {
+        // Uniform direction on the unit sphere: uniform height + uniform azimuth
+        let y = rng.range(-1.0, 1.0);
+        let (sin_phi, cos_phi) = rng.range(0.0, 2.0 * PI).sin_cos();
+        let ring = (1.0 - y * y).sqrt();
+        let dir = Vec3::new(ring * cos_phi, y, ring * sin_phi);
+
+        let size = config.radius * rng.range(0.001, 0.003);
+        let u = dir.any_orthonormal_vector() * size;
+        let v = dir.cross(u);
+        let center = dir * config.radius;
+        positions.extend([center - u - v, center + u - v, center + u + v, center - u + v]);
+        normals.extend([-dir; 4]);
+        // Slight brightness/temperature variation between stars
+        let brightness = rng.range(0.4, 1.0);
+        let warmth = rng.range(-0.1, 0.1);
+        colors.extend([[brightness + warmth, brightness, brightness - warmth, 1.0]; 4]);
+        let base = i * 4;
+        indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+    let mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::RENDER_WORLD)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_inserted_indices(Indices::U32(indices));
+
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(mesh),
+            material: materials.add(StandardMaterial {
+                base_color: Color::WHITE,
+                unlit: true,
+                cull_mode: None, // quads are seen from inside the sphere
+                ..default()
+            }),
+            ..default()
+        },
+        Skybox,
+        NotShadowCaster,
+    ));
+}
+
+/// System: when `mesh_detail` changes, swaps every body's sphere for one at the new
+/// tessellation. The old meshes are freed once their last handle is dropped.
+pub(crate) fn rebuild_body_meshes(
+    mut q: Query<(&BodyRadius, &mut Handle<Mesh>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    params: Res<SimulationParams>,
+    mut built: Local<Option<MeshDetail>>,
+) {
+    // Startup (and reset) already build at the current level
+    let current = *built.get_or_insert(params.mesh_detail);
+    if current == params.mesh_detail {
+        return;
+    }
+    *built = Some(params.mesh_detail);
+    for (radius, mut mesh) in &mut q {
+        *mesh = meshes.add(sphere_mesh(radius.0, params.mesh_detail));
+    }
+}
+
+/// Query data for a body `apply_body_scales` sizes.
+type ScaledBody = (&'static BodyRadius, &'static TrueRadius, &'static mut Transform, Has<Sun>, Option<&'static SizeScale>);
+
+/// System: sets each body's `Transform.scale`. Artistic mode uses the spawned mesh radii
+/// as-is (scale 1). Realistic mode keeps the Sun's sphere and sizes everything else by
+/// true radius relative to it (Sun ≈ 109 Earths), times `size_exaggeration` so planets
+/// remain visible. In both, the Sun itself is drawn at `sun_radius`; the planets keep
+/// their sizes, for "what if the Sun were bigger". A body's `SizeScale` multiplies on top.
+pub(crate) fn apply_body_scales(
+    mut q: Query<ScaledBody>,
+    sun_q: Query<(&BodyRadius, &TrueRadius), With<Sun>>,
+    params: Res<SimulationParams>,
+) {
+    let Ok((sun_radius, sun_true)) = sun_q.get_single() else { return };
+    for (radius, true_radius, mut transform, is_sun, size) in &mut q {
+        let scale = if is_sun {
+            params.sun_radius / radius.0
+        } else if params.realistic_scale {
+            sun_radius.0 * (true_radius.0 / sun_true.0) * params.size_exaggeration / radius.0
+        } else {
+            1.0
+        };
+        transform.scale = Vec3::splat(scale * size.map_or(1.0, |s| s.0));
+    }
+}
+
+/// Bodies with a show/hide checkbox in `ui_panel` (and, but for the Sun, an orbit-direction
+/// one): the original three, then `PLANETS`.
+pub(crate) fn toggleable_bodies() -> impl Iterator<Item = &'static str> {
+    ["Sun", "Earth", "Moon"].into_iter().chain(PLANETS.iter().map(|p| p.name))
+}
+
+/// System: hides the bodies named in `hidden_bodies` and shows the rest. Only their
+/// `Visibility` changes, so they keep moving and reappear exactly where they should be.
+/// Shown bodies get `Visible` rather than `Inherited`, so hiding Earth doesn't take the
+/// Moon (its descendant) with it; a body's own children (rings, markers) still follow it.
+pub(crate) fn apply_body_visibility(mut bodies: Query<(&Name, &mut Visibility), With<BodyRadius>>, params: Res<SimulationParams>) {
+    for (name, mut visibility) in &mut bodies {
+        let wanted = if params.hidden_bodies.iter().any(|hidden| hidden == name.as_str()) {
+            Visibility::Hidden
+        } else {
+            Visibility::Visible
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+}
+
+/// The asset stores `spawn_bodies` builds the scene into.
+#[derive(SystemParam)]
+pub(crate) struct SceneAssets<'w> {
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+    asset_server: Res<'w, AssetServer>,
+}
+
+/// Per-run state `reset_simulation` puts back to its defaults alongside the params.
+#[derive(SystemParam)]
+pub(crate) struct RunState<'w> {
+    clock: ResMut<'w, SimClock>,
+    eclipse: ResMut<'w, EclipseState>,
+    selected: ResMut<'w, Selected>,
+}
+
+/// The camera `reset_simulation` returns to its starting pose unless asked to keep it.
+#[derive(SystemParam)]
+pub(crate) struct CameraReset<'w, 's> {
+    camera_q: Query<'w, 's, &'static mut CameraController>,
+    tween: ResMut<'w, CameraTween>,
+}
+
+/// System: handles `ResetSimulation`: restores default params, clock, and per-feature
+/// state, despawns every `SceneRoot` hierarchy, and spawns the bodies afresh, so all
+/// bodies return to their starting angles. The camera is reset unless asked to keep it.
+pub(crate) fn reset_simulation(
+    mut events: EventReader<ResetSimulation>,
+    mut commands: Commands,
+    roots: Query<Entity, With<SceneRoot>>,
+    assets: SceneAssets,
+    mut params: ResMut<SimulationParams>,
+    run: RunState,
+    camera: CameraReset,
+) {
+    let SceneAssets { mut meshes, mut materials, asset_server } = assets;
+    let RunState { mut clock, mut eclipse, mut selected } = run;
+    let CameraReset { mut camera_q, mut tween } = camera;
+    let Some(&ResetSimulation { keep_camera }) = events.read().last() else { return };
+    for root in &roots {
+        commands.entity(root).despawn_recursive();
+    }
+    *params = SimulationParams::default();
+    *clock = SimClock::default();
+    *eclipse = EclipseState::default();
+    selected.0 = None; // follow-cam falls back to free once its target is gone
+    if !keep_camera {
+        tween.0 = None;
+        for mut ctrl in &mut camera_q {
+            *ctrl = CameraController::default();
+        }
+    }
+    spawn_bodies(&mut commands, &mut meshes, &mut materials, &asset_server, &params);
+}
+
+/// System: once a `TextureFallback` body's texture has loaded, drops the marker; if it
+/// failed, clears the texture and switches to the solid color instead. An unloadable
+/// texture would otherwise keep the material from ever being drawn.
+pub(crate) fn texture_fallback(
+    mut commands: Commands,
+    mut q: Query<(Entity, &Handle<StandardMaterial>, &TextureFallback, &mut BaseColor)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, material, fallback, mut base) in &mut q {
+        let Some(texture) = materials.get(material).and_then(|m| m.base_color_texture.as_ref()) else {
+            commands.entity(entity).remove::<TextureFallback>();
+            continue;
+        };
+        match asset_server.load_state(texture) {
+            LoadState::Loaded => {}
+            LoadState::Failed(err) => {
+                warn!("Texture unavailable, using a solid color instead: {err}");
+                if let Some(mat) = materials.get_mut(material) {
+                    mat.base_color_texture = None;
+                    mat.base_color = fallback.0;
+                }
+                base.0 = fallback.0;
+            }
+            _ => continue, // still loading
+        }
+        commands.entity(entity).remove::<TextureFallback>();
+    }
+}
@@ -0,0 +1,1274 @@
+//! Keyboard shortcuts and the egui panels.
+
+use bevy::diagnostic::{DiagnosticPath, DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::render::renderer::RenderDevice;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContexts};
+use std::f32::consts::{PI, TAU};
+use crate::{camera::*, eclipse::*, export::*, orbits::*, overlays::*, params::*, physics::*, render::*, scene::*, window::*};
+
+/// Slider over `SPEED_SCALE_RANGE`, optionally logarithmic (`log_sliders`). Log scaling
+/// through zero runs −5..−0.01, then 0, then 0.01..5, so slow speeds get most of the travel.
+fn speed_slider<'a>(value: &'a mut f32, log: bool, text: &str) -> egui::Slider<'a> {
+    egui::Slider::new(value, SPEED_SCALE_RANGE).logarithmic(log).smallest_positive(0.01).text(text)
+}
+
+/// What a keyboard shortcut does; dispatched by `keyboard_controls`.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum KeyAction {
+    OrbitFaster,
+    OrbitSlower,
+    SpinFaster,
+    SpinSlower,
+    ResetSpeeds,
+    TogglePause,
+    CameraPreset(CameraPreset),
+    FrameAll,
+    CycleSelection,
+    Screenshot, // handled by `take_screenshot`, which also runs while the UI is hidden
+    ToggleHelp,
+}
+
+/// One keyboard shortcut: the keys that trigger it, and how the help window lists it.
+struct KeyBinding {
+    keys: &'static [KeyCode],
+    shown: &'static str, // key names as written in the help window
+    action: KeyAction,
+    description: &'static str,
+}
+
+/// Every keyboard shortcut. `keyboard_controls` and `take_screenshot` look their keys up
+/// here and `help_window` lists the table, so a new row is documented automatically.
+const KEY_BINDINGS: &[KeyBinding] = &[
+    KeyBinding { keys: &[KeyCode::Space], shown: "Space", action: KeyAction::TogglePause, description: "Pause / resume" },
+    KeyBinding { keys: &[KeyCode::Equal, KeyCode::NumpadAdd], shown: "+", action: KeyAction::OrbitFaster, description: "Orbit speed +0.1 (Ctrl: +1)" },
+    KeyBinding { keys: &[KeyCode::Minus, KeyCode::NumpadSubtract], shown: "-", action: KeyAction::OrbitSlower, description: "Orbit speed −0.1 (Ctrl: −1)" },
+    KeyBinding { keys: &[KeyCode::BracketRight], shown: "]", action: KeyAction::SpinFaster, description: "Spin speed +0.1 (Ctrl: +1)" },
+    KeyBinding { keys: &[KeyCode::BracketLeft], shown: "[", action: KeyAction::SpinSlower, description: "Spin speed −0.1 (Ctrl: −1)" },
+    KeyBinding { keys: &[KeyCode::Digit0, KeyCode::Numpad0], shown: "0", action: KeyAction::ResetSpeeds, description: "Reset orbit and spin speeds to 1" },
+    KeyBinding { keys: &[KeyCode::Digit1], shown: "1", action: KeyAction::CameraPreset(CameraPreset::Default), description: "Camera: default view" },
+    KeyBinding { keys: &[KeyCode::Digit2], shown: "2", action: KeyAction::CameraPreset(CameraPreset::Top), description: "Camera: top view" },
+    KeyBinding { keys: &[KeyCode::Digit3], shown: "3", action: KeyAction::CameraPreset(CameraPreset::Side), description: "Camera: side view" },
+    KeyBinding { keys: &[KeyCode::Digit4], shown: "4", action: KeyAction::CameraPreset(CameraPreset::Earth), description: "Camera: Earth close-up" },
+    KeyBinding { keys: &[KeyCode::KeyF], shown: "F", action: KeyAction::FrameAll, description: "Camera: frame all bodies" },
+    KeyBinding { keys: &[KeyCode::Tab], shown: "Tab", action: KeyAction::CycleSelection, description: "Select the next body (Shift: previous)" },
+    KeyBinding { keys: &[KeyCode::F12], shown: "F12", action: KeyAction::Screenshot, description: "Save a screenshot" },
+    KeyBinding { keys: &[KeyCode::F1], shown: "F1", action: KeyAction::ToggleHelp, description: "Show / hide this help" },
+];
+
+/// Mouse controls, handled by `orbit_camera` and `pick_body`; listed by `help_window`.
+const MOUSE_CONTROLS: &[(&str, &str)] = &[
+    ("Left-drag", "Rotate the camera (flick and release to let it coast)"),
+    ("Right-drag", "Pan"),
+    ("Scroll", "Zoom"),
+    ("Ctrl+scroll", "Resize the selected body"),
+    ("Click", "Select a body (empty space clears)"),
+    ("Hover", "Name and distance from the Sun of the body under the cursor"),
+];
+
+impl KeyAction {
+    /// The keys bound to this action in `KEY_BINDINGS`.
+    pub(crate) fn keys(self) -> impl Iterator<Item = KeyCode> {
+        KEY_BINDINGS.iter().filter(move |b| b.action == self).flat_map(|b| b.keys.iter().copied())
+    }
+}
+
+/// Camera requests the keyboard shortcuts send.
+#[derive(SystemParam)]
+pub(crate) struct CameraRequests<'w> {
+    presets: EventWriter<'w, CameraPresetRequest>,
+    frame_all: EventWriter<'w, FrameAllRequest>,
+}
+
+/// System: runs the `KEY_BINDINGS` shortcuts pressed this frame (all but the screenshot).
+/// Speed nudges step 0.1, or 1.0 with Ctrl, clamped to the slider range. Tab moves
+/// `Selected` along `toggleable_bodies` (Shift-Tab back), wrapping at either end and
+/// skipping hidden bodies, and with `cycle_follows` the follow-cam goes with it. Ignored
+/// while egui has keyboard focus (e.g. typing in a text field).
+pub(crate) fn keyboard_controls(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut params: ResMut<SimulationParams>,
+    mut contexts: EguiContexts,
+    camera_requests: CameraRequests,
+    mut selected: ResMut<Selected>,
+    mut camera_mode: ResMut<CameraMode>,
+    bodies: Query<(Entity, &Name), With<BodyRadius>>,
+) {
+    let CameraRequests { mut presets, mut frame_all } = camera_requests;
+    if contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    // Ctrl rather than Shift for the coarse step: on most layouts `+` already needs Shift
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let step = if ctrl { 1.0 } else { 0.1 };
+    let (lo, hi) = (*SPEED_SCALE_RANGE.start(), *SPEED_SCALE_RANGE.end());
+    for binding in KEY_BINDINGS.iter().filter(|b| keys.any_just_pressed(b.keys.iter().copied())) {
+        match binding.action {
+            KeyAction::OrbitFaster => params.orbit_speed_scale = (params.orbit_speed_scale + step).clamp(lo, hi),
+            KeyAction::OrbitSlower => params.orbit_speed_scale = (params.orbit_speed_scale - step).clamp(lo, hi),
+            KeyAction::SpinFaster => params.spin_speed_scale = (params.spin_speed_scale + step).clamp(lo, hi),
+            KeyAction::SpinSlower => params.spin_speed_scale = (params.spin_speed_scale - step).clamp(lo, hi),
+            KeyAction::ResetSpeeds => {
+                params.orbit_speed_scale = 1.0;
+                params.spin_speed_scale = 1.0;
+            }
+            KeyAction::TogglePause => params.paused = !params.paused,
+            KeyAction::CameraPreset(preset) => {
+                presets.send(CameraPresetRequest(preset));
+            }
+            KeyAction::FrameAll => {
+                frame_all.send(FrameAllRequest);
+            }
+            KeyAction::CycleSelection => {
+                let order: Vec<Entity> = toggleable_bodies()
+                    .filter(|name| !params.hidden_bodies.iter().any(|hidden| hidden == name))
+                    .filter_map(|name| bodies.iter().find(|(_, n)| n.as_str() == name).map(|(entity, _)| entity))
+                    .collect();
+                if order.is_empty() {
+                    continue;
+                }
+                // From no selection (or one outside the cycle), Tab starts at the Sun, Shift-Tab at the end
+                let next = match order.iter().position(|&entity| Some(entity) == selected.0) {
+                    Some(index) if shift => (index + order.len() - 1) % order.len(),
+                    Some(index) => (index + 1) % order.len(),
+                    None if shift => order.len() - 1,
+                    None => 0,
+                };
+                selected.0 = Some(order[next]);
+                if params.cycle_follows {
+                    *camera_mode = CameraMode::Follow(order[next]);
+                }
+            }
+            KeyAction::Screenshot => {}
+            KeyAction::ToggleHelp => params.show_help = !params.show_help,
+        }
+    }
+}
+
+/// UI system: the "Help" window, a two-column list of `KEY_BINDINGS` and `MOUSE_CONTROLS`.
+pub(crate) fn help_window(mut contexts: EguiContexts, mut params: ResMut<SimulationParams>) {
+    if !params.show_help {
+        return;
+    }
+    let mut open = true;
+    egui::Window::new("Help")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.heading("Keyboard");
+            egui::Grid::new("help_keys").striped(true).show(ui, |ui| {
+                for binding in KEY_BINDINGS {
+                    ui.strong(binding.shown);
+                    ui.label(binding.description);
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            ui.heading("Mouse");
+            egui::Grid::new("help_mouse").striped(true).show(ui, |ui| {
+                for (control, description) in MOUSE_CONTROLS {
+                    ui.strong(*control);
+                    ui.label(*description);
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            ui.label("Everything else lives in the Simulation panel.");
+        });
+    if !open {
+        params.show_help = false;
+    }
+}
+
+/// UI system: paints each `BodyLabel` just above its body, on egui's background layer
+/// so windows stay on top. The world position is projected to screen space; labels behind
+/// the camera are skipped, and the font shrinks with distance.
+pub(crate) fn draw_labels(
+    mut contexts: EguiContexts,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<CameraController>>,
+    labels: Query<(&BodyLabel, &GlobalTransform, &BodyRadius, &InheritedVisibility)>,
+    params: Res<SimulationParams>,
+) {
+    if !params.show_labels {
+        return;
+    }
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), camera_q.get_single()) else { return };
+    let ratio = viewport_ratio(camera, window);
+    let painter = contexts
+        .ctx_mut()
+        .layer_painter(egui::LayerId::new(egui::Order::Background, egui::Id::new("body_labels")));
+    let eye = camera_transform.translation();
+    for (label, global, radius, _) in labels.iter().filter(|(.., visible)| visible.get()) {
+        let scale = global.compute_transform().scale.max_element();
+        let anchor = global.translation() + Vec3::Y * radius.0 * scale * 1.2;
+        let distance = anchor.distance(eye);
+        if (anchor - eye).dot(*camera_transform.forward()) <= 0.0 {
+            continue;
+        }
+        let Some(screen) = camera.world_to_viewport(camera_transform, anchor).map(|viewport| viewport / ratio) else { continue };
+        let size = (160.0 / distance).clamp(9.0, 18.0);
+        painter.text(
+            egui::pos2(screen.x, screen.y),
+            egui::Align2::CENTER_BOTTOM,
+            &label.0,
+            egui::FontId::proportional(size),
+            egui::Color32::from_rgba_unmultiplied(230, 230, 230, 200),
+        );
+    }
+}
+
+/// UI system: small top-right overlay with FPS, frame time, and entity count, read from
+/// Bevy's `DiagnosticsStore` (smoothed values; blank until the first samples arrive).
+pub(crate) fn perf_hud(
+    mut contexts: EguiContexts,
+    diagnostics: Res<DiagnosticsStore>,
+    params: Res<SimulationParams>,
+    camera_q: Query<&Camera, With<CameraController>>,
+) {
+    if !params.show_perf_hud {
+        return;
+    }
+    let reading = |path: &DiagnosticPath, precision: usize| match diagnostics.get(path).and_then(|d| d.smoothed()) {
+        Some(value) => format!("{value:.precision$}"),
+        None => "–".to_string(),
+    };
+    egui::Window::new("Performance")
+        .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
+        .resizable(false)
+        .title_bar(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(format!("FPS: {}", reading(&FrameTimeDiagnosticsPlugin::FPS, 0)));
+            ui.label(format!("Frame time: {} ms", reading(&FrameTimeDiagnosticsPlugin::FRAME_TIME, 2)));
+            ui.label(format!("Entities: {}", reading(&EntityCountDiagnosticsPlugin::ENTITY_COUNT, 0)));
+            if let Some(size) = camera_q.get_single().ok().and_then(Camera::physical_target_size) {
+                ui.label(format!("Scene: {}×{} ({:.0}%)", size.x, size.y, params.render_scale * 100.0));
+            }
+        });
+}
+
+/// UI system: small window showing the Moon phase as seen from Earth, derived each frame
+/// from the bodies' world positions (no extra simulation state), plus a painted disc.
+pub(crate) fn moon_phase_panel(
+    mut contexts: EguiContexts,
+    sun_q: Query<&GlobalTransform, With<Sun>>,
+    earth_q: Query<&GlobalTransform, With<Earth>>,
+    moon_q: Query<&GlobalTransform, With<Moon>>,
+) {
+    let (Ok(sun), Ok(earth), Ok(moon)) = (sun_q.get_single(), earth_q.get_single(), moon_q.get_single())
+    else {
+        return;
+    };
+    let geometry = Syzygy::new(sun.translation(), earth.translation(), moon.translation());
+    let phase = geometry.phase();
+    let name = MOON_PHASES[(phase * 8.0).round() as usize % MOON_PHASES.len()];
+
+    egui::Window::new("Moon phase")
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(name);
+            ui.label(format!("Illuminated: {:.0}%", geometry.illuminated_fraction() * 100.0));
+            paint_moon_disc(ui, phase, 32.0);
+        });
+}
+
+/// Paints a Moon disc of radius `r` lit according to `phase` (0 = new, 0.5 = full).
+/// Northern-hemisphere view: the right limb is lit while waxing, the left while waning.
+/// The lit region is bounded by the limb and a half-ellipse terminator; it's filled as
+/// horizontal strips so crescents (non-convex) render correctly.
+fn paint_moon_disc(ui: &mut egui::Ui, phase: f32, r: f32) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(2.0 * r, 2.0 * r), egui::Sense::hover());
+    let painter = ui.painter();
+    let center = rect.center();
+    painter.circle_filled(center, r, egui::Color32::from_gray(40));
+
+    let side = if phase < 0.5 { 1.0 } else { -1.0 };
+    let k = (TAU * phase).cos(); // terminator semi-axis: 1 = on the lit limb, -1 = on the far limb
+    let lit = egui::Color32::from_gray(230);
+    let mut mesh = egui::Mesh::default();
+    const SLICES: u32 = 48;
+    for i in 0..=SLICES {
+        let a = PI * (i as f32 / SLICES as f32 - 0.5);
+        let (y, x) = (r * a.sin(), r * a.cos());
+        mesh.colored_vertex(center + egui::vec2(side * x, y), lit);
+        mesh.colored_vertex(center + egui::vec2(side * k * x, y), lit);
+        if i > 0 {
+            let base = 2 * i;
+            mesh.add_triangle(base - 2, base - 1, base);
+            mesh.add_triangle(base - 1, base + 1, base);
+        }
+    }
+    painter.add(egui::Shape::mesh(mesh));
+}
+
+/// UI system: while the cursor rests over a body (the same ray-sphere test as `pick_body`)
+/// and no mouse button is held, shows a tooltip at the pointer with the body's name and its
+/// distance from the Sun, in scene units and in the AU that distance maps back to. Nothing
+/// shows over egui windows or empty space.
+pub(crate) fn hover_tooltip(
+    mut contexts: EguiContexts,
+    buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<CameraController>>,
+    bodies: Query<PickableBody>,
+    names: Query<&Name>,
+    sun_q: Query<&GlobalTransform, With<Sun>>,
+) {
+    let Ok(window) = windows.get_single() else { return };
+    let Some(cursor) = window.cursor_position() else { return };
+    if buttons.get_pressed().next().is_some() {
+        return; // dragging the camera
+    }
+    let ctx = contexts.ctx_mut();
+    if ctx.wants_pointer_input() || ctx.is_pointer_over_area() {
+        return;
+    }
+    let Ok((camera, camera_transform)) = camera_q.get_single() else { return };
+    let Some(ray) = camera.viewport_to_world(camera_transform, cursor * viewport_ratio(camera, window)) else { return };
+    let Some(entity) = pick_nearest(ray, &bodies) else { return };
+    let (Ok(name), Ok((_, global, ..)), Ok(sun)) = (names.get(entity), bodies.get(entity), sun_q.get_single()) else { return };
+    let distance = global.translation().distance(sun.translation());
+    egui::show_tooltip_at_pointer(ctx, egui::LayerId::background(), egui::Id::new("body_hover"), |ui| {
+        ui.strong(name.as_str());
+        if distance > f32::EPSILON {
+            ui.label(format!("{distance:.2} from the Sun (≈ {:.2} AU)", scene_to_au(distance)));
+        }
+    });
+}
+
+/// Query data for the body whose elements `ElementSources::elements` works out.
+type ElementBody = (
+    &'static Transform,
+    &'static GlobalTransform,
+    Option<&'static Parent>,
+    Option<&'static EllipticalOrbit>,
+    Option<&'static Velocity>,
+    Option<&'static Mass>,
+    Has<Moon>,
+);
+
+/// Query data for a possible primary (the Sun, or Earth for the Moon) in physics mode.
+type PrimaryBody = (&'static GlobalTransform, &'static Velocity, &'static Mass, Has<Sun>, Has<Earth>);
+
+/// Everything `selection_panel` reads to work out the selected body's orbital elements.
+#[derive(SystemParam)]
+pub(crate) struct ElementSources<'w, 's> {
+    bodies: Query<'w, 's, ElementBody>,
+    pivots: Query<'w, 's, (&'static Orbit, &'static GlobalTransform, Option<&'static Parent>)>,
+    frames: Query<'w, 's, &'static GlobalTransform>,
+    primaries: Query<'w, 's, PrimaryBody>,
+}
+
+impl ElementSources<'_, '_> {
+    /// `entity`'s elements for how it's moving now: osculating ones from its state relative
+    /// to its primary in physics mode (the Moon's is Earth, everyone else's the Sun), the
+    /// `EllipticalOrbit`'s own shape while it rides one, or a circle of the pivot radius.
+    /// The direction of travel comes from the orbit's rate, so a body set retrograde reads
+    /// 180° minus its plane's tilt. None for bodies not in orbit (the Sun).
+    fn elements(&self, entity: Entity, params: &SimulationParams) -> Option<OrbitalElements> {
+        let (local, global, parent, ellipse, velocity, mass, is_moon) = self.bodies.get(entity).ok()?;
+        if let (true, Some(velocity), Some(mass)) = (params.physics_mode, velocity, mass) {
+            let (primary, primary_velocity, primary_mass, ..) = self.primaries.iter().find(|&(.., is_sun, is_earth)| if is_moon { is_earth } else { is_sun })?;
+            let r = global.translation() - primary.translation();
+            if r == Vec3::ZERO {
+                return None; // the Sun itself
+            }
+            let mu = params.gravitational_constant * (primary_mass.0 + mass.0);
+            return Some(OrbitalElements::from_state(r, velocity.0 - primary_velocity.0, mu));
+        }
+        let parent_frame = parent.and_then(|parent| self.frames.get(parent.get()).ok());
+        let direction = |rate: f32, normal: Vec3| {
+            let tilt = normal.angle_between(Vec3::Y);
+            if rate < 0.0 { PI - tilt } else { tilt }
+        };
+        if let Some(ellipse) = ellipse.filter(|ellipse| ellipse.active) {
+            let in_plane = Quat::from_rotation_x(ellipse.inclination).inverse() * local.translation;
+            let normal = parent_frame.map_or(Quat::IDENTITY, |frame| frame.to_scale_rotation_translation().1) * Quat::from_rotation_x(ellipse.inclination) * Vec3::Y;
+            let rate = if params.use_kepler_motion { ellipse.mean_motion } else { ellipse.angular_speed };
+            return Some(OrbitalElements {
+                semi_major_axis: ellipse.a,
+                eccentricity: ellipse.focal_offset() / ellipse.a,
+                inclination: direction(rate, normal),
+                true_anomaly: (-in_plane.z).atan2(in_plane.x).rem_euclid(TAU),
+                circular: false,
+            });
+        }
+        let (orbit, pivot, plane) = self.pivots.get(parent?.get()).ok()?;
+        // Measured in the frame the pivot turns in, where it yaws +X toward −Z
+        let plane = plane.and_then(|plane| self.frames.get(plane.get()).ok()).map_or(Quat::IDENTITY, |frame| frame.to_scale_rotation_translation().1);
+        let offset = plane.inverse() * (global.translation() - pivot.translation());
+        Some(OrbitalElements {
+            semi_major_axis: local.translation.length(),
+            eccentricity: 0.0,
+            inclination: direction(orbit.angular_speed, *pivot.up()),
+            true_anomaly: (-offset.z).atan2(offset.x).rem_euclid(TAU),
+            circular: true,
+        })
+    }
+}
+
+/// Query data for the body shown in `selection_panel`.
+type SelectedBody = (
+    &'static Name,
+    &'static Transform,
+    &'static GlobalTransform,
+    &'static TrackedMotion,
+    Option<&'static Handle<StandardMaterial>>,
+    Has<WireframeBody>,
+    Option<&'static BodyRadius>,
+    Option<&'static SizeScale>,
+);
+
+/// What `selection_panel`'s controls change on the selected body: its components (size
+/// reset, wireframe) and material, with the render device to tell if wireframes work.
+#[derive(SystemParam)]
+pub(crate) struct BodyEdits<'w, 's> {
+    commands: Commands<'w, 's>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+    device: Option<Res<'w, RenderDevice>>,
+}
+
+/// UI system: side panel describing the selected body, including its orbital elements
+/// (see `ElementSources::elements`). Clears a stale selection (e.g. the entity was despawned).
+pub(crate) fn selection_panel(
+    mut contexts: EguiContexts,
+    edits: BodyEdits,
+    mut selected: ResMut<Selected>,
+    mut camera_mode: ResMut<CameraMode>,
+    bodies: Query<SelectedBody>,
+    sources: ElementSources,
+    params: Res<SimulationParams>,
+) {
+    let BodyEdits { mut commands, mut materials, device } = edits;
+    let Some(entity) = selected.0 else { return };
+    let Ok((name, local, global, motion, material, wireframe, radius, size)) = bodies.get(entity) else {
+        selected.0 = None;
+        return;
+    };
+    egui::SidePanel::right("selection").show(contexts.ctx_mut(), |ui| {
+        ui.heading(name.as_str());
+        // Pivots sit at the orbit center, so the local offset is the orbit radius
+        ui.label(format!("Orbit radius: {:.2}", local.translation.length()));
+        ui.label(format!("Speed: {:.2} units/s", motion.velocity.length()));
+        let p = global.translation();
+        ui.label(format!("Position: ({:.2}, {:.2}, {:.2})", p.x, p.y, p.z));
+        if let Some(radius) = radius {
+            let drawn = radius.0 * global.compute_transform().scale.max_element();
+            ui.horizontal(|ui| {
+                ui.label(format!("Radius: {drawn:.3}"))
+                    .on_hover_text("Ctrl+scroll over the scene to resize");
+                if let Some(&SizeScale(factor)) = size {
+                    ui.label(format!("(×{factor:.2})"));
+                    if ui.small_button("Reset size").clicked() {
+                        commands.entity(entity).remove::<SizeScale>();
+                    }
+                }
+            });
+        }
+        if let Some(elements) = sources.elements(entity, &params) {
+            ui.separator();
+            ui.label("Orbital elements");
+            egui::Grid::new("orbital_elements").show(ui, |ui| {
+                ui.label("Semi-major axis");
+                ui.label(format!("{:.3}", elements.semi_major_axis));
+                ui.end_row();
+                ui.label("Eccentricity");
+                ui.label(format!("{:.4}", elements.eccentricity));
+                ui.end_row();
+                ui.label("Inclination");
+                ui.label(format!("{:.2}°", elements.inclination.to_degrees()));
+                ui.end_row();
+                ui.label("True anomaly");
+                let anomaly = ui.label(format!("{:.1}°", elements.true_anomaly.to_degrees()));
+                if elements.circular {
+                    anomaly.on_hover_text("Circular orbit: no periapsis, so measured from the pivot's start direction");
+                }
+                ui.end_row();
+            });
+            if params.physics_mode && !elements.circular {
+                ui.small("Osculating: the orbit it would keep if the other pulls stopped now");
+            }
+            ui.separator();
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Follow").clicked() {
+                *camera_mode = CameraMode::Follow(entity);
+            }
+            if ui.button("Deselect").clicked() {
+                selected.0 = None;
+            }
+        });
+        let mut show_wireframe = wireframe || params.wireframe;
+        let line_mode = line_mode_supported(device.as_deref());
+        let toggle = ui.add_enabled(line_mode && !params.wireframe, egui::Checkbox::new(&mut show_wireframe, "Wireframe"));
+        let reason = if line_mode { "On for every body in the main panel" } else { "This GPU can't draw line polygons" };
+        if toggle.on_disabled_hover_text(reason).changed() {
+            if show_wireframe {
+                commands.entity(entity).try_insert(WireframeBody);
+            } else {
+                commands.entity(entity).remove::<WireframeBody>();
+            }
+        }
+        // Edits the body's material asset in place (shared handles, e.g. extra moons,
+        // change together). Only written on change, so the asset isn't re-uploaded every frame.
+        let Some(handle) = material else { return };
+        let Some(current) = materials.get(handle) else { return };
+        let (mut roughness, mut metallic) = (current.perceptual_roughness, current.metallic);
+        ui.separator();
+        ui.label("Material");
+        let changed = ui.add(egui::Slider::new(&mut roughness, 0.089..=1.0).text("Roughness")).changed()
+            | ui.add(egui::Slider::new(&mut metallic, 0.0..=1.0).text("Metallic")).changed();
+        if changed {
+            if let Some(material) = materials.get_mut(handle) {
+                material.perceptual_roughness = roughness;
+                material.metallic = metallic;
+            }
+        }
+    });
+}
+
+/// Query data for a body's orbital period/speed readout in `ui_panel`.
+type OrbitReadout = (
+    &'static Name,
+    &'static TrackedMotion,
+    Option<&'static Parent>,
+    Option<&'static EllipticalOrbit>,
+    Has<Earth>,
+    Has<Moon>,
+);
+
+/// Query filter for the three original bodies.
+type SunEarthMoon = Or<(With<Sun>, With<Earth>, With<Moon>)>;
+
+/// Event writers for the requests `ui_panel` sends, bundled to stay under Bevy's
+/// system-parameter limit.
+#[derive(SystemParam)]
+pub(crate) struct UiRequests<'w> {
+    state_file: EventWriter<'w, StateFileRequest>,
+    reset: EventWriter<'w, ResetSimulation>,
+    screenshot: EventWriter<'w, ScreenshotRequest>,
+    export_gltf: EventWriter<'w, ExportGltfRequest>,
+    start_phase: EventWriter<'w, StartPhaseChanged>,
+    camera_preset: EventWriter<'w, CameraPresetRequest>,
+    frame_all: EventWriter<'w, FrameAllRequest>,
+    warp: EventWriter<'w, WarpToTime>,
+    circularize: EventWriter<'w, CircularizeOrbits>,
+}
+
+/// Read-only state `ui_panel` reports, bundled for the same reason as `UiRequests`.
+#[derive(SystemParam)]
+pub(crate) struct UiStatus<'w> {
+    clock: Res<'w, SimClock>,
+    real_time: Res<'w, Time<Real>>,
+    eclipse: Res<'w, EclipseState>,
+    overlaps: Res<'w, Overlaps>,
+    unbound: Res<'w, UnboundBodies>,
+    render_device: Option<Res<'w, RenderDevice>>, // for `line_mode_supported`
+}
+
+/// Resources besides `SimulationParams` that `ui_panel` edits in place, bundled like
+/// `UiRequests`.
+#[derive(SystemParam)]
+pub(crate) struct UiSettings<'w> {
+    screenshot_settings: ResMut<'w, ScreenshotSettings>,
+    window_config: ResMut<'w, WindowConfig>,
+    csv: ResMut<'w, CsvExport>,
+    camera_mode: ResMut<'w, CameraMode>,
+}
+
+/// Scene queries `ui_panel` lists, reads off, or (for spins) adjusts.
+#[derive(SystemParam)]
+pub(crate) struct UiBodies<'w, 's> {
+    bodies: Query<'w, 's, (Entity, &'static Name), With<BodyRadius>>,
+    spins: Query<'w, 's, (&'static Name, &'static mut Spin, Has<Earth>), SunEarthMoon>,
+    pivots: Query<'w, 's, (&'static Orbit, Has<EarthPivot>, Has<MoonPivot>)>,
+    readouts: Query<'w, 's, OrbitReadout, With<BodyRadius>>,
+}
+
+/// Form values `ui_panel` keeps between frames.
+#[derive(Default)]
+pub(crate) struct UiForm {
+    keep_camera: bool,       // the reset button's "Keep camera"
+    warp_target: (f64, f64), // "Go to" years and days since t = 0
+}
+
+/// UI system: exposes sliders to tweak the simulation at runtime.
+/// In ECS terms, this system mutates the global `SimulationParams` Resource. The widgets
+/// edit a copy that is written back only when it differs: handing them the resource
+/// itself would mark it changed every frame, and the systems that only act on a change
+/// (`params.is_changed()`) would never skip.
+pub(crate) fn ui_panel(
+    mut contexts: EguiContexts,
+    mut shared_params: ResMut<SimulationParams>,
+    mut requests: UiRequests,
+    mut form: Local<UiForm>,
+    settings: UiSettings,
+    status: UiStatus,
+    scene: UiBodies,
+) {
+    let UiSettings { mut screenshot_settings, mut window_config, mut csv, mut camera_mode } = settings;
+    let UiBodies { bodies, mut spins, pivots, readouts } = scene;
+    let UiForm { keep_camera, warp_target } = &mut *form;
+    let UiStatus { clock, real_time, eclipse, overlaps, unbound, render_device } = status;
+    let mut params = shared_params.clone();
+    egui::Window::new("Simulation").show(contexts.ctx_mut(), |ui| {
+        if ui.button("Help (F1)").clicked() {
+            params.show_help = !params.show_help;
+        }
+        ui.heading("Clock");
+        let years = clock.years();
+        let day = years.rem_euclid(1.0) * SimClock::DAYS_PER_YEAR; // stays in range when rewound before year 1
+        ui.label(format!("Year {}, Day {}", years.floor() as i64 + 1, day.floor() as i64 + 1));
+        ui.label(format!("Sim time: {:.2} d", clock.sim_seconds / SECONDS_PER_DAY));
+        ui.label(format!("Wall clock: {:.1} s", real_time.elapsed_seconds()));
+        ui.horizontal(|ui| {
+            ui.label("Go to");
+            ui.add(egui::DragValue::new(&mut warp_target.0).speed(0.1).suffix(" y"));
+            ui.add(egui::DragValue::new(&mut warp_target.1).range(0.0..=SimClock::DAYS_PER_YEAR).suffix(" d"));
+            // No closed form for the n-body integrator
+            if ui.add_enabled(!params.physics_mode, egui::Button::new("Go")).clicked() {
+                let (years, days) = *warp_target;
+                requests.warp.send(WarpToTime { years: years + days / SimClock::DAYS_PER_YEAR });
+            }
+        });
+        ui.checkbox(&mut params.paused, "Paused");
+        ui.checkbox(&mut params.fixed_timestep, "Fixed timestep (deterministic)");
+        ui.add(egui::Slider::new(&mut params.max_frame_dt, 0.005..=0.5).logarithmic(true).text("Max frame step (s)"));
+        ui.horizontal(|ui| {
+            if ui.button("Save state").clicked() {
+                requests.state_file.send(StateFileRequest::Save);
+            }
+            if ui.button("Load state").clicked() {
+                requests.state_file.send(StateFileRequest::Load);
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Reset simulation").clicked() {
+                requests.reset.send(ResetSimulation { keep_camera: *keep_camera });
+            }
+            ui.checkbox(keep_camera, "Keep camera");
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Screenshot (F12)").clicked() {
+                requests.screenshot.send(ScreenshotRequest);
+            }
+            ui.checkbox(&mut screenshot_settings.include_ui, "Include UI");
+            if ui.button("Export glTF").on_hover_text("Save the scene as it is now to a .glb file").clicked() {
+                requests.export_gltf.send(ExportGltfRequest);
+            }
+        });
+        // Edited on a copy: `apply_window_config` saves once the resource stops changing
+        let mut edited = *window_config;
+        ui.horizontal(|ui| {
+            ui.label("Window");
+            // Only the windowed size; fullscreen takes the monitor's
+            ui.add_enabled_ui(!edited.fullscreen, |ui| {
+                ui.add(egui::DragValue::new(&mut edited.width).range(WINDOW_WIDTH_RANGE).suffix(" w"));
+                ui.add(egui::DragValue::new(&mut edited.height).range(WINDOW_HEIGHT_RANGE).suffix(" h"));
+            });
+            ui.checkbox(&mut edited.fullscreen, "Fullscreen");
+        });
+        window_config.set_if_neq(edited);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut csv.record_csv, "Record CSV to");
+            // The path is fixed while a file is open
+            ui.add_enabled(csv.writer.is_none(), egui::TextEdit::singleline(&mut csv.path).desired_width(120.0));
+        });
+        ui.add(egui::Slider::new(&mut csv.interval, 0.0..=5.0).text("CSV interval (s, 0 = every frame)"));
+
+        ui.separator();
+        ui.heading("Camera");
+        let follow_label = |mode: CameraMode| match mode {
+            CameraMode::Free => "Free".to_string(),
+            CameraMode::Follow(e) => bodies.get(e).map_or("?".to_string(), |(_, n)| format!("Follow {n}")),
+        };
+        egui::ComboBox::from_label("Mode")
+            .selected_text(follow_label(*camera_mode))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut *camera_mode, CameraMode::Free, "Free");
+                for (entity, _) in &bodies {
+                    let mode = CameraMode::Follow(entity);
+                    ui.selectable_value(&mut *camera_mode, mode, follow_label(mode));
+                }
+            });
+        ui.checkbox(&mut params.cycle_follows, "Tab selection moves the follow-cam");
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Reference frame")
+                .selected_text(params.reference_frame.as_deref().unwrap_or("World"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut params.reference_frame, None, "World");
+                    for name in toggleable_bodies() {
+                        ui.selectable_value(&mut params.reference_frame, Some(name.to_string()), name);
+                    }
+                });
+            ui.add_enabled(
+                params.reference_frame.is_some(),
+                egui::Checkbox::new(&mut params.frame_trails, "Trails in this frame"),
+            );
+        });
+        ui.horizontal(|ui| {
+            for preset in CameraPreset::ALL {
+                if ui.button(preset.label()).clicked() {
+                    requests.camera_preset.send(CameraPresetRequest(preset));
+                }
+            }
+            if ui.button("Frame all").clicked() {
+                requests.frame_all.send(FrameAllRequest);
+            }
+        });
+        ui.add(egui::Slider::new(&mut params.camera_fov_deg, 20.0..=90.0).text("FOV (°)"));
+        ui.add(egui::Slider::new(&mut params.camera_near, 0.01..=1.0).logarithmic(true).text("Near plane"));
+        ui.add(egui::Slider::new(&mut params.camera_far, 100.0..=10_000.0).logarithmic(true).text("Far plane"));
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut params.camera_inertia, "Inertia");
+            ui.add_enabled(
+                params.camera_inertia,
+                egui::Slider::new(&mut params.camera_damping, 0.5..=20.0).logarithmic(true).text("Damping (1/s)"),
+            );
+        });
+        ui.add(egui::Slider::new(&mut params.camera_roll_deg, -180.0..=180.0).text("Roll (°)"));
+        ui.checkbox(&mut params.look_along_orbit, "Look along Earth's orbit")
+            .on_hover_text("Fly alongside Earth, facing its direction of travel; drag to look around it");
+
+        ui.separator();
+        ui.heading("Speeds & scales");
+        // Simulated time per real second; the sliders below multiply on top
+        let mut days_per_second = params.time_scale / SECONDS_PER_DAY;
+        let time_scale = egui::Slider::new(&mut days_per_second, 1.0 / 1440.0..=730.0)
+            .logarithmic(true)
+            .custom_formatter(|days, _| format_duration(days * SECONDS_PER_DAY))
+            .text("per second");
+        if ui.add(time_scale).changed() {
+            params.time_scale = days_per_second * SECONDS_PER_DAY;
+        }
+        ui.horizontal(|ui| {
+            for (label, scale) in TIME_SCALE_PRESETS {
+                if ui.selectable_label(params.time_scale == scale, label).clicked() {
+                    params.time_scale = scale;
+                }
+            }
+        });
+        ui.checkbox(&mut params.log_sliders, "Logarithmic speed/radius sliders");
+        let log = params.log_sliders;
+        ui.add(speed_slider(&mut params.orbit_speed_scale, log, "Orbit speed ×"));
+        ui.add(speed_slider(&mut params.planet_orbit_scale, log, "  Planet orbits ×"));
+        ui.add(speed_slider(&mut params.moon_orbit_scale, log, "  Moon orbit ×"));
+        egui::CollapsingHeader::new(format!("Orbit directions ({} retrograde)", params.retrograde_bodies.len())).show(ui, |ui| {
+            ui.label("Retrograde bodies orbit backward; the speed sliders still multiply (rewinding runs them forward).");
+            egui::Grid::new("orbit_directions").show(ui, |ui| {
+                for (i, name) in toggleable_bodies().skip(1).enumerate() {
+                    let mut retrograde = params.retrograde_bodies.iter().any(|r| r == name);
+                    if ui.checkbox(&mut retrograde, name).changed() {
+                        params.retrograde_bodies.retain(|r| r != name);
+                        if retrograde {
+                            params.retrograde_bodies.push(name.to_string());
+                        }
+                    }
+                    if i % 4 == 3 {
+                        ui.end_row();
+                    }
+                }
+            });
+            if params.physics_mode {
+                ui.label("Physics mode: gravity sets the directions.");
+            }
+        });
+        egui::CollapsingHeader::new("Moon resonance").show(ui, |ui| {
+            ui.checkbox(&mut params.moon_resonance, "Lock the Moon's period to a ratio");
+            ui.add_enabled_ui(params.moon_resonance, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut params.resonance_ratio[0]).range(1..=1000));
+                    ui.label(":");
+                    ui.add(egui::DragValue::new(&mut params.resonance_ratio[1]).range(1..=1000));
+                    egui::ComboBox::from_id_source("resonance_target")
+                        .selected_text(params.resonance_target.label())
+                        .show_ui(ui, |ui| {
+                            for target in ResonanceTarget::ALL {
+                                ui.selectable_value(&mut params.resonance_target, target, target.label());
+                            }
+                        });
+                });
+                let [periods, orbits] = params.resonance_ratio;
+                ui.label(format!("{orbits} Moon orbits every {periods} {}", params.resonance_target.label().to_lowercase()));
+                ui.horizontal(|ui| {
+                    for (label, target, ratio) in [
+                        ("1:12 year", ResonanceTarget::EarthOrbit, [1, 12]),
+                        ("1:13 year", ResonanceTarget::EarthOrbit, [1, 13]),
+                        ("27:1 day", ResonanceTarget::EarthRotation, [27, 1]),
+                    ] {
+                        if ui.button(label).clicked() {
+                            (params.resonance_target, params.resonance_ratio) = (target, ratio);
+                        }
+                    }
+                });
+            });
+            // Effective (signed) rates, sliders included, as in the orbital readouts
+            let rate_of = |is_moon: bool| {
+                readouts.iter().find(|r| if is_moon { r.5 } else { r.4 }).and_then(|(_, _, parent, ellipse, ..)| match ellipse {
+                    Some(e) if e.active => Some(e.mean_motion * params.orbit_scale(is_moon)),
+                    _ => parent
+                        .and_then(|p| pivots.get(p.get()).ok())
+                        .map(|(o, _, is_moon_pivot)| o.angular_speed * params.orbit_scale(is_moon_pivot)),
+                })
+            };
+            if let (Some(earth), Some(moon)) = (rate_of(false), rate_of(true)) {
+                if earth != 0.0 {
+                    let sidereal = moon / earth;
+                    // New moons come round at the Moon's rate relative to the Sun's (Earth's) direction
+                    ui.label(format!("{:.2} sidereal months ({:.2} synodic) per Earth year", sidereal.abs(), (sidereal - 1.0).abs()));
+                }
+            }
+        });
+        ui.add(speed_slider(&mut params.spin_speed_scale, log, "Spin speed ×"));
+        // Per-body base spin rates; the global "Spin speed ×" still multiplies them
+        let turns_per_day = (SECONDS_PER_DAY / std::f64::consts::TAU) as f32; // per rad per sim second
+        for (name, mut spin, is_earth) in &mut spins {
+            let mut turns = spin.angular_speed * turns_per_day;
+            let slider = egui::Slider::new(&mut turns, -4.0..=4.0)
+                .logarithmic(log)
+                .smallest_positive(0.01)
+                .text(format!("{name} spin (turns/day)"));
+            if ui.add(slider).changed() {
+                spin.angular_speed = turns / turns_per_day;
+            }
+            if is_earth {
+                // `Spin` turns Earth against the fixed stars (sidereal); the Sun also drifts
+                // round at the orbital rate, so noon to noon uses their difference
+                let sidereal = spin.angular_speed * params.spin_speed_scale;
+                let orbital = pivots
+                    .iter()
+                    .find(|&(_, is_earth_pivot, _)| is_earth_pivot)
+                    .map_or(0.0, |(o, ..)| o.angular_speed * params.orbit_scale(false));
+                let solar = sidereal - orbital;
+                let period = |rate: f32| if rate != 0.0 { format!("{:.2} h", TAU / rate.abs() / 3600.0) } else { "∞".into() };
+                ui.label(format!("Sidereal day: {}, solar day: {}", period(sidereal), period(solar)));
+                let rate = if params.solar_day { solar } else { sidereal };
+                if orbital != 0.0 {
+                    let kind = if params.solar_day { "solar" } else { "sidereal" };
+                    ui.label(format!("{:.1} {kind} days per year", (rate / orbital).abs()));
+                }
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut params.solar_day, "Count solar days");
+                    ui.checkbox(&mut params.show_day_markers, "Day markers on Earth");
+                });
+            }
+        }
+
+        ui.checkbox(&mut params.axial_precession, "Precess Earth's axis");
+        ui.add_enabled(
+            params.axial_precession,
+            egui::Slider::new(&mut params.precession_deg_per_year, 0.1..=90.0).logarithmic(true).text("Precession (°/year)"),
+        );
+
+        // Period from the driving angular speed (pivot, or the ellipse's mean motion), speed
+        // from the measured world-space motion, so Earth's varies along a Kepler ellipse
+        egui::CollapsingHeader::new("Orbital readouts").show(ui, |ui| {
+            egui::Grid::new("orbit_readouts").striped(true).show(ui, |ui| {
+                ui.label("Body");
+                ui.label("Period");
+                ui.label("Speed");
+                ui.end_row();
+                for (name, motion, parent, ellipse, _, is_moon) in &readouts {
+                    let rate = match ellipse {
+                        Some(e) if e.active => Some(e.mean_motion * params.orbit_scale(is_moon)),
+                        _ => parent
+                            .and_then(|p| pivots.get(p.get()).ok())
+                            .map(|(o, _, is_moon_pivot)| o.angular_speed * params.orbit_scale(is_moon_pivot)),
+                    };
+                    let period = match rate.map(f32::abs) {
+                        _ if params.physics_mode => "gravity".to_string(),
+                        Some(r) if r > 0.0 => format_duration(f64::from(TAU / r)),
+                        Some(_) => "∞".to_string(),
+                        None => "—".to_string(),
+                    };
+                    ui.label(name.as_str());
+                    ui.label(period);
+                    ui.label(format!("{:.3} u/s", motion.velocity.length()));
+                    ui.end_row();
+                }
+            });
+        });
+
+        ui.separator();
+        ui.heading("Distances");
+        let log = params.log_sliders;
+        ui.add(egui::Slider::new(&mut params.earth_orbit_radius, EARTH_ORBIT_RADIUS_RANGE).logarithmic(log).text("Earth radius"));
+        ui.add(egui::Slider::new(&mut params.moon_orbit_radius, MOON_ORBIT_RADIUS_RANGE).logarithmic(log).text("Moon radius"));
+        ui.checkbox(&mut params.moon_elliptical, "Elliptical Moon orbit");
+        ui.add_enabled(
+            params.moon_elliptical,
+            egui::Slider::new(&mut params.moon_eccentricity, 0.0..=0.7).text("Moon eccentricity"),
+        );
+        let earth = readouts.iter().find_map(|r| r.4.then_some(r.1.last_position).flatten());
+        let moon = readouts.iter().find_map(|r| r.5.then_some(r.1.last_position).flatten());
+        if let (Some(earth), Some(moon)) = (earth, moon) {
+            let distance = earth.distance(moon);
+            let moon_ellipse = readouts.iter().find_map(|r| r.5.then_some(r.3).flatten()).filter(|e| e.active);
+            match moon_ellipse.map(EllipticalOrbit::apsides) {
+                Some((perigee, apogee)) => {
+                    ui.label(format!("Earth–Moon distance {distance:.2} (perigee {perigee:.2}, apogee {apogee:.2})"));
+                    // Within the closest tenth of the perigee–apogee range
+                    if apogee > perigee && distance <= perigee + 0.1 * (apogee - perigee) {
+                        ui.colored_label(egui::Color32::LIGHT_YELLOW, "Supermoon: the Moon is near perigee");
+                    }
+                }
+                None => {
+                    ui.label(format!("Earth–Moon distance {distance:.2}"));
+                }
+            }
+        }
+        ui.add(egui::Slider::new(&mut params.sun_radius, 0.2..=5.0).logarithmic(log).text("Sun size (brighter when bigger)"));
+        for (a, b) in &overlaps.0 {
+            ui.colored_label(egui::Color32::YELLOW, format!("⚠ {a} and {b} overlap"));
+        }
+        ui.checkbox(&mut params.clamp_radii, "Keep the Moon clear of Earth and the Sun");
+        ui.checkbox(&mut params.smooth_radius_changes, "Glide to new radii");
+        egui::CollapsingHeader::new("Orbits").show(ui, |ui| {
+            ui.label("Radius and speed of each planet's orbit; the speed multiplies its real period's rate.");
+            egui::ScrollArea::vertical().id_source("planet_orbits").max_height(200.0).show(ui, |ui| {
+                egui::Grid::new("planet_orbits_grid").num_columns(3).show(ui, |ui| {
+                    let rows = params.planet_orbit_radii.iter_mut().zip(&mut params.planet_speed_scales);
+                    for (planet, (radius, speed)) in PLANETS.iter().zip(rows) {
+                        ui.label(planet.name);
+                        ui.add(egui::Slider::new(radius, PLANET_ORBIT_RADIUS_RANGE).logarithmic(log).text("radius"));
+                        ui.add(egui::Slider::new(speed, PLANET_SPEED_RANGE).logarithmic(log).text("speed ×"));
+                        ui.end_row();
+                    }
+                });
+            });
+            if ui.button("Reset orbits").clicked() {
+                let defaults = SimulationParams::default();
+                params.planet_orbit_radii = defaults.planet_orbit_radii;
+                params.planet_speed_scales = defaults.planet_speed_scales;
+            }
+        });
+        ui.collapsing("Start phases", |ui| {
+            ui.label("Angle along each orbit at t = 0; moving a slider puts the body there.");
+            ui.add_enabled_ui(!params.physics_mode, |ui| {
+                let mut phase_slider = |ui: &mut egui::Ui, value: &mut f32, name: &str, target| {
+                    if ui.add(egui::Slider::new(value, 0.0..=360.0).text(format!("{name} (°)"))).changed() {
+                        requests.start_phase.send(StartPhaseChanged(target));
+                    }
+                };
+                phase_slider(ui, &mut params.earth_start_phase_deg, "Earth", PhaseTarget::Earth);
+                phase_slider(ui, &mut params.moon_start_phase_deg, "Moon", PhaseTarget::Moon);
+                for (index, (planet, phase)) in PLANETS.iter().zip(&mut params.planet_start_phases_deg).enumerate() {
+                    phase_slider(ui, phase, planet.name, PhaseTarget::Planet(index));
+                }
+            });
+        });
+        ui.checkbox(&mut params.tidally_locked, "Tidally locked Moon (red spot faces Earth)");
+        let mut inclination_deg = params.moon_inclination.to_degrees();
+        if ui.add(egui::Slider::new(&mut inclination_deg, 0.0..=30.0).text("Moon inclination (°)")).changed() {
+            params.moon_inclination = inclination_deg.to_radians();
+        }
+
+        ui.separator();
+        ui.heading("Eclipses");
+        ui.label(match eclipse.current {
+            Eclipse::None => "No eclipse",
+            Eclipse::Solar => "Solar eclipse!",
+            Eclipse::Lunar => "Lunar eclipse!",
+        });
+        if let Some((kind, at)) = eclipse.last {
+            ui.label(format!("Last: {kind:?} on sim day {:.1}", at / SECONDS_PER_DAY));
+        }
+        ui.label(if eclipse.umbra_on_earth { "Moon's umbra touches Earth" } else { "Moon's umbra misses Earth" });
+        // The rendered shadow is only as sharp as the Sun's shadow map
+        if !params.shadows {
+            ui.colored_label(egui::Color32::YELLOW, "Shadows are off: the Moon's shadow isn't drawn");
+        } else if eclipse.moon_shadow_texels < 8.0 {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!("Moon's shadow is only ~{:.0} shadow-map texels wide; raise the shadow map", eclipse.moon_shadow_texels),
+            );
+        }
+        ui.add(egui::Slider::new(&mut params.eclipse_threshold_deg, 0.5..=10.0).text("Alignment tolerance (°)"));
+        ui.label("Eclipses need the Moon near a node of its inclined orbit.");
+        ui.checkbox(&mut params.eclipse_tint, "Tint eclipsed body")
+            .on_hover_text("With shadows on, a solar eclipse shows as the Moon's shadow on Earth instead");
+        ui.checkbox(&mut params.auto_slow_mo, "Auto slow-mo near alignments");
+        ui.add_enabled(
+            params.auto_slow_mo,
+            egui::Slider::new(&mut params.slow_mo_floor, 0.01..=1.0).logarithmic(true).text("Slowest speed ×"),
+        );
+        if clock.time_dilation < 0.999 {
+            ui.label(format!("Slow-mo: {:.2}× speed", clock.time_dilation));
+        }
+        egui::ComboBox::from_label("Pause at")
+            .selected_text(params.pause_at.label())
+            .show_ui(ui, |ui| {
+                for target in AlignmentTarget::ALL {
+                    ui.selectable_value(&mut params.pause_at, target, target.label());
+                }
+            });
+
+        ui.separator();
+        ui.heading("Body sizes");
+        ui.checkbox(&mut params.realistic_scale, "True relative sizes (Sun ≈ 109× Earth)");
+        ui.add_enabled(
+            params.realistic_scale,
+            egui::Slider::new(&mut params.size_exaggeration, 1.0..=200.0).text("Size exaggeration ×"),
+        );
+        egui::ComboBox::from_label("Mesh detail")
+            .selected_text(format!("{:?}", params.mesh_detail))
+            .show_ui(ui, |ui| {
+                for detail in MeshDetail::ALL {
+                    ui.selectable_value(&mut params.mesh_detail, detail, format!("{detail:?}"));
+                }
+            });
+        ui.add_enabled(line_mode_supported(render_device.as_deref()), egui::Checkbox::new(&mut params.wireframe, "Wireframe (all bodies)"))
+            .on_hover_text("Outline the triangles over the shading; select a body to toggle just that one")
+            .on_disabled_hover_text("This GPU can't draw line polygons");
+        egui::ComboBox::from_label("Antialiasing (MSAA)")
+            .selected_text(params.msaa.label())
+            .show_ui(ui, |ui| {
+                for level in MsaaLevel::ALL {
+                    ui.selectable_value(&mut params.msaa, level, level.label());
+                }
+            });
+        ui.add(egui::Slider::new(&mut params.render_scale, 0.25..=1.0).text("Render scale"))
+            .on_hover_text("Render the 3D scene below window resolution and stretch it up; the UI stays sharp. The performance HUD shows the result");
+
+        ui.separator();
+        ui.heading("Lighting");
+        ui.horizontal(|ui| {
+            ui.color_edit_button_rgb(&mut params.ambient_color);
+            ui.label("Ambient color");
+        });
+        ui.add(egui::Slider::new(&mut params.ambient_brightness, 0.0..=500.0).logarithmic(true).text("Ambient brightness"));
+        ui.add(egui::Slider::new(&mut params.sun_light_intensity, 0.0..=5e7).logarithmic(true).text("Sunlight (lm)"));
+        ui.add(egui::Slider::new(&mut params.fill_illuminance, 0.0..=150_000.0).text("Fill light (lux)"));
+        ui.add(egui::Slider::new(&mut params.fill_light_pitch_deg, -90.0..=90.0).text("Fill light pitch (°)"));
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut params.night_lights, "City lights");
+            ui.add_enabled(
+                params.night_lights,
+                egui::Slider::new(&mut params.night_lights_strength, 0.0..=4.0).text("Brightness"),
+            );
+        })
+        .response
+        .on_hover_text("Earth's night side, from assets/textures/earth_night.png");
+        ui.add(egui::Slider::new(&mut params.fill_light_yaw_deg, -180.0..=180.0).text("Fill light yaw (°)"));
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut params.shadows, "Shadows");
+            ui.add_enabled_ui(params.shadows, |ui| {
+                egui::ComboBox::from_label("Shadow map")
+                    .selected_text(format!("{0}×{0}", params.shadow_map_size))
+                    .show_ui(ui, |ui| {
+                        for size in SHADOW_MAP_SIZES {
+                            ui.selectable_value(&mut params.shadow_map_size, size, format!("{size}×{size}"));
+                        }
+                    });
+            });
+        })
+        .response
+        .on_hover_text("Higher resolution gives crisper eclipse shadows; lower is faster");
+        egui::ComboBox::from_label("Planet colors")
+            .selected_text(params.color_scheme.label())
+            .show_ui(ui, |ui| {
+                for scheme in ColorScheme::ALL {
+                    ui.selectable_value(&mut params.color_scheme, scheme, scheme.label());
+                }
+            });
+        ui.horizontal(|ui| {
+            ui.color_edit_button_rgb(&mut params.background_color);
+            ui.label("Background");
+        });
+        ui.checkbox(&mut params.gradient_background, "Gradient background");
+        if params.gradient_background {
+            ui.horizontal(|ui| {
+                ui.color_edit_button_rgb(&mut params.gradient_top);
+                ui.label("Top");
+                ui.color_edit_button_rgb(&mut params.gradient_bottom);
+                ui.label("Bottom");
+            });
+        }
+
+        ui.separator();
+        ui.heading("Sound");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut params.hum_muted, "Mute");
+            ui.add_enabled(!params.hum_muted, egui::Slider::new(&mut params.hum_volume, 0.0..=1.0).text("Hum volume"));
+        })
+        .response
+        .on_hover_text("Ambient drone that rises in pitch with the orbit speed");
+
+        ui.separator();
+        ui.heading("Small bodies");
+        ui.add(egui::Slider::new(&mut params.belt_count, 0..=2000).text("Asteroids"));
+        ui.add(egui::Slider::new(&mut params.belt_inner_au, 1.6..=5.0).text("Inner edge (AU)"));
+        ui.add(egui::Slider::new(&mut params.belt_outer_au, 1.6..=5.0).text("Outer edge (AU)"));
+
+        ui.checkbox(&mut params.show_comet, "Comet (e = 0.85, with anti-sunward tail)");
+        ui.add(egui::Slider::new(&mut params.extra_earth_moons, 0..=6).text("Extra Earth moons"));
+        ui.add(egui::Slider::new(&mut params.jupiter_moons, 0..=8).text("Jupiter moons"));
+
+        ui.separator();
+        ui.heading("Overlays");
+        egui::CollapsingHeader::new(format!("Visible bodies ({} hidden)", params.hidden_bodies.len())).show(ui, |ui| {
+            ui.label("Hidden bodies keep moving, so they reappear in place.");
+            egui::Grid::new("body_visibility").show(ui, |ui| {
+                for (i, name) in toggleable_bodies().enumerate() {
+                    let mut shown = !params.hidden_bodies.iter().any(|hidden| hidden == name);
+                    if ui.checkbox(&mut shown, name).changed() {
+                        params.hidden_bodies.retain(|hidden| hidden != name);
+                        if !shown {
+                            params.hidden_bodies.push(name.to_string());
+                        }
+                    }
+                    if i % 4 == 3 {
+                        ui.end_row();
+                    }
+                }
+            });
+            if ui.add_enabled(!params.hidden_bodies.is_empty(), egui::Button::new("Show all")).clicked() {
+                params.hidden_bodies.clear();
+            }
+        });
+        ui.add(egui::Slider::new(&mut params.bloom_intensity, 0.0..=1.0).text("Sun bloom"));
+        ui.checkbox(&mut params.show_perf_hud, "Show performance HUD");
+        ui.checkbox(&mut params.show_labels, "Show body labels");
+        ui.checkbox(&mut params.show_lagrange, "Show Sun–Earth Lagrange points");
+        ui.checkbox(&mut params.show_barycenter, "Show Earth–Moon barycenter");
+        ui.add_enabled_ui(!params.physics_mode, |ui| {
+            ui.checkbox(&mut params.barycentric_motion, "Earth wobbles about the barycenter")
+                .on_disabled_hover_text("Physics mode integrates the wobble itself");
+        });
+        if params.show_barycenter || params.barycentric_motion {
+            ui.add(egui::Slider::new(&mut params.moon_mass, 0.0001..=1.0).logarithmic(true).text("Moon mass"));
+        }
+        ui.checkbox(&mut params.show_velocity_arrows, "Show velocity arrows");
+        ui.add_enabled(
+            params.show_velocity_arrows,
+            egui::Slider::new(&mut params.velocity_arrow_scale, 0.05..=5.0).logarithmic(true).text("Arrow length (per u/s)"),
+        );
+        ui.checkbox(&mut params.show_ecliptic_grid, "Show ecliptic grid");
+        ui.add_enabled_ui(params.show_ecliptic_grid, |ui| {
+            ui.add(egui::Slider::new(&mut params.grid_extent, 5.0..=100.0).text("Grid extent"));
+            ui.add(egui::Slider::new(&mut params.grid_spacing, 0.25..=10.0).logarithmic(true).text("Grid spacing"));
+        });
+        ui.checkbox(&mut params.show_nodes, "Show lines of nodes (cyan ascending, magenta descending)")
+            .on_hover_text("Where inclined orbits cross the ecliptic; eclipses need the Moon near a node");
+        ui.checkbox(&mut params.show_orbit_circles, "Show orbit paths");
+        ui.add_enabled_ui(params.show_orbit_circles, |ui| {
+            ui.add(egui::Slider::new(&mut params.orbit_path_samples, 8..=512).logarithmic(true).text("Path samples"));
+            ui.horizontal(|ui| {
+                ui.color_edit_button_rgba_unmultiplied(&mut params.orbit_path_color);
+                ui.label("Path color");
+            });
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut params.show_retrograde, "Mars as seen from Earth (retrograde loops)");
+            ui.add_enabled(params.show_retrograde, egui::Checkbox::new(&mut params.retrograde_spread, "Spread over time"));
+        });
+        ui.checkbox(&mut params.debug_day_night, "Debug day/night (sun-angle tint + terminators)");
+        ui.checkbox(&mut params.show_minimap, "Show top-down minimap");
+        ui.checkbox(&mut params.show_trails, "Show orbit trails");
+        ui.add(egui::Slider::new(&mut params.trail_length, 2..=OrbitTrail::MAX_LEN).text("Trail length"));
+        ui.add_enabled_ui(params.show_trails, |ui| {
+            ui.checkbox(&mut params.trail_fade, "Fade trails out");
+            ui.add_enabled(
+                params.trail_fade,
+                egui::Slider::new(&mut params.trail_fade_secs, 0.5..=60.0).logarithmic(true).text("Fade time (real s)"),
+            );
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut params.trail_gradient, "Gradient");
+                ui.add_enabled_ui(params.trail_gradient, |ui| {
+                    ui.label("new");
+                    ui.color_edit_button_rgb(&mut params.trail_gradient_start);
+                    ui.label("old");
+                    ui.color_edit_button_rgb(&mut params.trail_gradient_end);
+                });
+            });
+        });
+
+        ui.separator();
+        ui.checkbox(&mut params.physics_mode, "N-body gravity (Sun, Earth, Moon)");
+        if params.physics_mode {
+            ui.label("Orbits follow mutual gravity; the other planets hold still.");
+            ui.add(egui::Slider::new(&mut params.gravitational_constant, 0.05..=10.0).logarithmic(true).text("G"));
+            ui.add(egui::Slider::new(&mut params.gravity_softening, 0.0..=0.5).text("Softening ε"))
+                .on_hover_text("Caps the pull at close range so near-collisions stay calm; larger values weaken tight orbits");
+            ui.add(egui::Slider::new(&mut params.sun_mass, 0.05..=20.0).logarithmic(true).text("Sun mass"));
+            ui.add(egui::Slider::new(&mut params.earth_mass, 0.001..=5.0).logarithmic(true).text("Earth mass"));
+            ui.add(egui::Slider::new(&mut params.moon_mass, 0.0001..=1.0).logarithmic(true).text("Moon mass"));
+            if ui.button("Reset to stable circular").clicked() {
+                requests.circularize.send(CircularizeOrbits);
+            }
+            for (body, primary) in &unbound.0 {
+                ui.colored_label(egui::Color32::YELLOW, format!("⚠ {body} is unbound (escaping {primary})"));
+            }
+        }
+        ui.checkbox(&mut params.use_elliptical_orbit, "Use elliptical orbit for Earth");
+        egui::CollapsingHeader::new("Elliptical planets").show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for (planet, elliptical) in PLANETS.iter().zip(&mut params.elliptical_planets) {
+                    ui.checkbox(elliptical, format!("{} (e = {})", planet.name, planet.eccentricity));
+                }
+            });
+        });
+        ui.checkbox(&mut params.use_kepler_motion, "Kepler motion (speeds up near perihelion)");
+        ui.add(egui::Slider::new(&mut params.earth_eccentricity, 0.0..=0.9).text("Orbit eccentricity"));
+        ui.add(egui::Slider::new(&mut params.earth_orbit_inclination_deg, -30.0..=30.0).text("Orbit inclination (°)"));
+        if params.use_kepler_motion {
+            ui.label("Solves M = E − e·sin(E) each frame; the Sun sits at a focus.");
+        } else {
+            ui.label("Ellipse uses x = a cos(θ) − c, z = b sin(θ), Sun at a focus. Timing is parametric.");
+        }
+        if params.use_elliptical_orbit {
+            if let Some(ellipse) = readouts.iter().find_map(|r| r.4.then_some(r.3).flatten()) {
+                let (perihelion, aphelion) = ellipse.apsides();
+                ui.label(format!("Perihelion {perihelion:.2}, aphelion {aphelion:.2} (yellow / red markers)"));
+            }
+        }
+    });
+    shared_params.set_if_neq(params);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use crate::headless_app;
+
+    #[test]
+    fn ellipse_inclination_matches_the_osculating_one() {
+        for (tilt_deg, retrograde) in [(0.0, false), (20.0, false), (20.0, true)] {
+            let mut app = headless_app();
+            let mut params = app.world_mut().resource_mut::<SimulationParams>();
+            params.use_elliptical_orbit = true;
+            params.earth_orbit_inclination_deg = tilt_deg;
+            if retrograde {
+                params.retrograde_bodies.push("Earth".to_string());
+            }
+            for _ in 0..5 {
+                app.update();
+            }
+            let world = app.world_mut();
+            let earth = world.query_filtered::<Entity, With<Earth>>().single(world);
+            let shown = world
+                .run_system_once(move |sources: ElementSources, params: Res<SimulationParams>| sources.elements(earth, &params))
+                .expect("Earth has elements");
+            assert!(!shown.circular, "tilt {tilt_deg}°: expected the ellipse branch");
+            let before = world.get::<GlobalTransform>(earth).unwrap().translation();
+            app.update();
+            let world = app.world_mut();
+            let after = world.get::<GlobalTransform>(earth).unwrap().translation();
+            let sun = world.query_filtered::<&GlobalTransform, With<Sun>>().single(world).translation();
+            // Only the plane and sense of h = r × v matter for the inclination, not μ
+            let osculating = OrbitalElements::from_state(after - sun, after - before, 1.0);
+            assert!(
+                (shown.inclination - osculating.inclination).abs() < 1e-2,
+                "tilt {tilt_deg}°, retrograde {retrograde}: shown {}°, osculating {}°",
+                shown.inclination.to_degrees(),
+                osculating.inclination.to_degrees(),
+            );
+        }
+    }
+}
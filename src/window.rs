@@ -0,0 +1,171 @@
+//! Window size and fullscreen settings, saved between runs.
+
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowMode, WindowOccluded};
+use bevy::winit::WinitWindows;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use crate::params::*;
+
+/// Window settings file, relative to the working directory; rewritten whenever the UI changes them.
+const WINDOW_CONFIG_FILE: &str = "window.ron";
+
+/// Window sizes (logical pixels) the UI and `--size` accept: from a small laptop window
+/// up to 8K.
+pub(crate) const WINDOW_WIDTH_RANGE: RangeInclusive<f32> = 320.0..=7680.0;
+pub(crate) const WINDOW_HEIGHT_RANGE: RangeInclusive<f32> = 240.0..=4320.0;
+
+/// Resource: window size and fullscreen choice. Starts from `WINDOW_CONFIG_FILE`, then the
+/// command line (`--size WxH`, `--fullscreen`, `--windowed`); `apply_window_config` pushes
+/// changes onto the primary `Window` and saves them, so the app reopens the same way.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub width: f32,       // logical pixels
+    pub height: f32,      // logical pixels
+    pub fullscreen: bool, // borderless fullscreen on the window's current monitor
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self { width: 1280.0, height: 720.0, fullscreen: false } // Bevy's default window size
+    }
+}
+
+/// A windowless run requested on the command line, instead of the interactive app.
+#[derive(Clone, Copy)]
+pub(crate) enum HeadlessRun {
+    Frames(u32), // `--headless [FRAMES]`: step the simulation, then exit
+    Bench(u32),  // `--bench [STEPS]`: time the stepping, print a summary, then exit
+}
+
+impl WindowConfig {
+    /// The saved settings with command-line overrides on top, plus the windowless run if
+    /// `--headless [FRAMES]` (default 600) or `--bench [STEPS]` (default 2000) was given.
+    /// Runs before the logger exists, so problems go to stderr; a missing file just means
+    /// the defaults.
+    pub(crate) fn from_file_and_args() -> (Self, Option<HeadlessRun>) {
+        let mut config = match std::fs::read_to_string(WINDOW_CONFIG_FILE) {
+            Ok(text) => ron::from_str(&text).unwrap_or_else(|err| {
+                eprintln!("Ignoring {WINDOW_CONFIG_FILE}: {err}");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+        let mut headless = None;
+        let mut args = std::env::args().skip(1).peekable();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => {
+                    let frames = args.next_if(|next| !next.starts_with("--"));
+                    headless = Some(HeadlessRun::Frames(frames.and_then(|f| f.parse().ok()).unwrap_or(600)));
+                }
+                "--bench" => {
+                    let steps = args.next_if(|next| !next.starts_with("--"));
+                    headless = Some(HeadlessRun::Bench(steps.and_then(|s| s.parse().ok()).unwrap_or(2000)));
+                }
+                "--fullscreen" => config.fullscreen = true,
+                "--windowed" => config.fullscreen = false,
+                "--size" => {
+                    let size = args.next().and_then(|size| {
+                        let (w, h) = size.split_once('x')?;
+                        Some((w.parse().ok()?, h.parse().ok()?))
+                    });
+                    match size {
+                        Some((width, height)) if WINDOW_WIDTH_RANGE.contains(&width) && WINDOW_HEIGHT_RANGE.contains(&height) => {
+                            (config.width, config.height) = (width, height);
+                        }
+                        Some((width, height)) => eprintln!(
+                            "Ignoring --size {width}x{height}: the width must be in {WINDOW_WIDTH_RANGE:?} and the height in {WINDOW_HEIGHT_RANGE:?}; keeping {}x{}",
+                            config.width, config.height,
+                        ),
+                        None => eprintln!("--size expects WIDTHxHEIGHT, e.g. --size 1920x1080"),
+                    }
+                }
+                other => eprintln!("Ignoring unknown argument {other}"),
+            }
+        }
+        (config, headless)
+    }
+
+    pub(crate) fn mode(&self) -> WindowMode {
+        if self.fullscreen { WindowMode::BorderlessFullscreen } else { WindowMode::Windowed }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+        std::fs::write(WINDOW_CONFIG_FILE, text).map_err(|e| e.to_string())
+    }
+}
+
+/// System: suspends the simulation (`SimClock::suspended`) while the primary window is
+/// minimized — or has a zero size or is reported occluded, for platforms that can't say
+/// "minimized" — and for the frame it comes back on, whose delta would span the whole
+/// absence. On restore, also drops the fixed-timestep accumulator, so `FixedUpdate`
+/// doesn't replay that time as a burst of catch-up steps. Runs in `PreUpdate`, ahead of
+/// every stepping system.
+pub(crate) fn suspend_while_minimized(
+    mut clock: ResMut<SimClock>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut occlusions: EventReader<WindowOccluded>,
+    windows: Query<(Entity, &Window), With<PrimaryWindow>>,
+    winit_windows: NonSend<WinitWindows>,
+    mut occluded: Local<bool>,
+    mut was_minimized: Local<bool>,
+) {
+    let Ok((entity, window)) = windows.get_single() else { return };
+    if let Some(event) = occlusions.read().filter(|e| e.window == entity).last() {
+        *occluded = event.occluded;
+    }
+    let iconified = winit_windows.get_window(entity).and_then(|w| w.is_minimized()).unwrap_or(false);
+    let minimized = iconified || *occluded || window.physical_width() == 0 || window.physical_height() == 0;
+    if *was_minimized && !minimized {
+        let overstep = fixed_time.overstep();
+        fixed_time.discard_overstep(overstep);
+    }
+    if clock.suspended != (minimized || *was_minimized) {
+        clock.suspended = minimized || *was_minimized;
+    }
+    *was_minimized = minimized;
+}
+
+/// System: applies `WindowConfig` changes to the primary window and saves them once the
+/// values settle (not every frame of a slider drag). Borderless fullscreen needs a monitor:
+/// if winit can't find one for the window, falls back to windowed mode with a warning.
+pub(crate) fn apply_window_config(
+    mut windows: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+    winit_windows: NonSend<WinitWindows>,
+    mut config: ResMut<WindowConfig>,
+    mut unsaved: Local<bool>,
+    mut monitor_checked: Local<bool>,
+) {
+    let Ok((entity, mut window)) = windows.get_single_mut() else { return };
+    if config.is_changed() {
+        *monitor_checked = false;
+    }
+    // Until winit has created the window there's nothing to ask; check again next frame
+    if let (true, false, Some(winit_window)) = (config.fullscreen, *monitor_checked, winit_windows.get_window(entity)) {
+        *monitor_checked = true;
+        if winit_window.current_monitor().or_else(|| winit_window.primary_monitor()).is_none() {
+            warn!("No monitor found for borderless fullscreen; staying windowed");
+            config.fullscreen = false;
+        }
+    }
+    if !config.is_changed() {
+        if std::mem::take(&mut *unsaved) {
+            match config.save() {
+                Ok(()) => info!("Saved window settings to {WINDOW_CONFIG_FILE}"),
+                Err(err) => warn!("Could not save {WINDOW_CONFIG_FILE}: {err}"),
+            }
+        }
+        return;
+    }
+    if window.mode != config.mode() {
+        window.mode = config.mode();
+    }
+    if !config.fullscreen && (window.resolution.width(), window.resolution.height()) != (config.width, config.height) {
+        window.resolution.set(config.width, config.height);
+    }
+    // The startup values came from the file and command line; only save what the UI changes
+    *unsaved = !config.is_added();
+}
@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+use orbit_sun_earth_moon::headless_app;
+
+/// World position of the body named `name`.
+fn position(app: &mut App, name: &str) -> Vec3 {
+    let mut bodies = app.world_mut().query::<(&Name, &GlobalTransform)>();
+    let (_, transform) = bodies.iter(app.world()).find(|(n, _)| n.as_str() == name).expect("body is spawned");
+    transform.translation()
+}
+
+#[test]
+fn bodies_advance_along_their_orbits() {
+    let mut app = headless_app();
+    for _ in 0..3 {
+        app.update(); // spawn the scene and record its initial poses
+    }
+    let (earth, moon) = (position(&mut app, "Earth"), position(&mut app, "Moon"));
+    for _ in 0..60 {
+        app.update();
+    }
+    let (earth_later, moon_later) = (position(&mut app, "Earth"), position(&mut app, "Moon"));
+    assert!(earth.distance(earth_later) > 1e-4, "Earth stayed at {earth}");
+    assert!(moon.distance(moon_later) > 1e-4, "the Moon stayed at {moon}");
+    // Still on its circle around the Sun at the origin
+    assert!((earth_later.length() - earth.length()).abs() < 1e-3);
+}